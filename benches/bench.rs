@@ -1,53 +1,162 @@
+// With no engine features enabled there is nothing to benchmark
+#![cfg_attr(
+    not(any(
+        feature = "gear",
+        feature = "gear32",
+        feature = "gear128",
+        feature = "bup",
+        feature = "buzhash",
+        feature = "rabin",
+        feature = "rsync",
+        feature = "rabinkarp",
+        feature = "cyclicpoly",
+        feature = "polyhash",
+        feature = "crc32"
+    )),
+    allow(unused)
+)]
+
 use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
 use nanorand::Rng;
 use rollsum::Engine;
+#[cfg(feature = "bup")]
+use rollsum::{windowed::BupFn, Windowed};
 
-fn bench_roll_byte(c: &mut Criterion) {
-    const SIZE: usize = 128 * 1024;
+const SIZE: usize = 128 * 1024;
 
+fn random_data() -> Vec<u8> {
     let mut data = vec![0u8; SIZE];
     let mut rng = nanorand::WyRand::new_seed(0x01020304);
     rng.fill_bytes(&mut data);
+    data
+}
 
-    let mut group = c.benchmark_group("roll");
-    group.throughput(Throughput::Bytes(SIZE as u64));
+fn zero_data() -> Vec<u8> {
+    vec![0u8; SIZE]
+}
 
-    macro_rules! bench_engine {
-        ($name:ident) => {{
-            group.bench_function(concat!(stringify!($name), "/byte_by_byte"), |b| {
-                let mut engine = rollsum::$name::new();
-                b.iter(|| {
-                    for _ in 0..SIZE {
-                        engine.roll_byte(black_box(0));
-                    }
+fn bench_roll_byte(c: &mut Criterion) {
+    let patterns: [(&str, Vec<u8>); 2] = [("random", random_data()), ("zeros", zero_data())];
+
+    for (pattern, data) in &patterns {
+        let mut group = c.benchmark_group(format!("roll/{}", pattern));
+        group.throughput(Throughput::Bytes(SIZE as u64));
+
+        macro_rules! bench_engine {
+            ($name:literal, $new:expr) => {{
+                group.bench_function(concat!($name, "/byte_by_byte"), |b| {
+                    let mut engine = $new;
+                    b.iter(|| {
+                        for &byte in data.iter() {
+                            engine.roll_byte(black_box(byte));
+                        }
+                    });
+                });
+
+                group.bench_function(concat!($name, "/all"), |b| {
+                    let mut engine = $new;
+                    b.iter(|| {
+                        engine.roll(black_box(data));
+                        black_box(engine.digest());
+                    });
+                });
+
+                group.bench_function(concat!($name, "/split"), |b| {
+                    let mut engine = $new;
+                    b.iter(|| {
+                        let mut remaining = black_box(&data[..]);
+                        while let Some((new_i, digest)) = engine.find_chunk_edge(remaining) {
+                            black_box((new_i, digest));
+                            remaining = &remaining[new_i..];
+                        }
+                    });
                 });
-            });
 
-            group.bench_function(concat!(stringify!($name), "/all"), |b| {
-                let mut engine = rollsum::$name::new();
+                group.bench_function(concat!($name, "/split_cond"), |b| {
+                    let mut engine = $new;
+                    b.iter(|| {
+                        let mut remaining = black_box(&data[..]);
+                        while let Some((new_i, digest)) =
+                            engine.find_chunk_edge_cond(remaining, |e| e.digest() & 0x1fff == 0)
+                        {
+                            black_box((new_i, digest));
+                            remaining = &remaining[new_i..];
+                        }
+                    });
+                });
+
+                group.bench_function(concat!($name, "/split_mask"), |b| {
+                    let mut engine = $new;
+                    b.iter(|| {
+                        let mut remaining = black_box(&data[..]);
+                        while let Some(new_i) = engine.find_chunk_edge_mask(remaining, 0x1fff, 0) {
+                            black_box(new_i);
+                            remaining = &remaining[new_i..];
+                        }
+                    });
+                });
+            }};
+        }
+
+        #[cfg(feature = "gear")]
+        bench_engine!("Gear", rollsum::Gear::new());
+        #[cfg(feature = "gear32")]
+        bench_engine!("Gear32", rollsum::Gear32::new());
+        #[cfg(feature = "gear128")]
+        bench_engine!("Gear128", rollsum::Gear128::new());
+        #[cfg(feature = "bup")]
+        bench_engine!("Bup", rollsum::Bup::new());
+        #[cfg(feature = "bup")]
+        bench_engine!("BupWide", rollsum::BupWide::new());
+        #[cfg(feature = "buzhash")]
+        bench_engine!("Buzhash", rollsum::Buzhash::new());
+        #[cfg(feature = "rabin")]
+        bench_engine!("Rabin", rollsum::Rabin::new());
+        #[cfg(feature = "rsync")]
+        bench_engine!("RsyncSum", rollsum::RsyncSum::new());
+        #[cfg(feature = "rabinkarp")]
+        bench_engine!("RabinKarp", rollsum::RabinKarp::new());
+        #[cfg(feature = "cyclicpoly")]
+        bench_engine!("CyclicPoly", rollsum::CyclicPoly::new());
+        #[cfg(feature = "polyhash")]
+        bench_engine!("PolyHash", rollsum::PolyHash::new());
+        #[cfg(feature = "crc32")]
+        bench_engine!("RollingCrc32", rollsum::RollingCrc32::new());
+        // Uses the SSE 4.2 instruction with the `crc32-hw` feature
+        #[cfg(feature = "crc32")]
+        bench_engine!(
+            "RollingCrc32C",
+            <rollsum::RollingCrc32>::with_polynomial(rollsum::crc32::CASTAGNOLI)
+        );
+
+        // `Bup` rebuilt on `Windowed`, to measure the cost of the abstraction
+        #[cfg(feature = "bup")]
+        {
+            group.bench_function("WindowedBup/byte_by_byte", |b| {
+                let mut engine = Windowed::<BupFn, 64>::default();
                 b.iter(|| {
-                    engine.roll(black_box(&data));
-                    black_box(engine.digest());
+                    for &byte in data.iter() {
+                        engine.roll_byte(black_box(byte));
+                    }
                 });
             });
 
-            group.bench_function(concat!(stringify!($name), "/split"), |b| {
-                let mut engine = rollsum::$name::new();
+            group.bench_function("WindowedBup/split_cond", |b| {
+                let mut engine = Windowed::<BupFn, 64>::default();
                 b.iter(|| {
                     let mut remaining = black_box(&data[..]);
-                    while let Some((new_i, digest)) = engine.find_chunk_edge(remaining) {
+                    while let Some((new_i, digest)) =
+                        engine.find_chunk_edge_cond(remaining, |e| e.digest() & 0x1fff == 0)
+                    {
                         black_box((new_i, digest));
                         remaining = &remaining[new_i..];
                     }
                 });
             });
-        }};
-    }
+        }
 
-    #[cfg(feature = "gear")]
-    bench_engine!(Gear);
-    #[cfg(feature = "bup")]
-    bench_engine!(Bup);
+        group.finish();
+    }
 }
 
 criterion_group!(benches, bench_roll_byte);
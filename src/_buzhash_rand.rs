@@ -0,0 +1,259 @@
+
+const T : [u32; 256] = [
+0x040da3d7,
+0x0ea60ed3,
+0x7a001237,
+0x0d81f1ad,
+0xd50e4737,
+0x3ecf7879,
+0x7b907ecb,
+0x484d2a3b,
+0x074c67e0,
+0x0c7a5064,
+0xbef65747,
+0x4d787a28,
+0x6f2b03fe,
+0x8cf7850c,
+0x32c74e17,
+0x452ac926,
+0x47488803,
+0x02b54d82,
+0x5db8d6aa,
+0xf6087fab,
+0x5836178d,
+0xfe7c8ea9,
+0xcda0cebb,
+0xb80ad33e,
+0x6b33543f,
+0x03944f33,
+0xc9e746ff,
+0x5243a792,
+0x9f612040,
+0x9f9339af,
+0x175ac8b9,
+0x1a8ecf3c,
+0xff4a5ad2,
+0xe169858e,
+0x0a01e136,
+0x5bd5f881,
+0x3c8c1853,
+0x3ea29f3a,
+0xcdfe099a,
+0x254a3d63,
+0x270c2f92,
+0x506321ef,
+0x89f95579,
+0x120a652e,
+0x1564cdec,
+0xf8ac4e20,
+0xc19d079a,
+0xe164e59c,
+0x918cedd2,
+0x174d97ab,
+0x098e66d7,
+0x35289d41,
+0xf02ad85f,
+0x8b4ddac6,
+0x73b70c14,
+0x5ce07d05,
+0x42b54285,
+0x20edee1d,
+0x84b77995,
+0xb6ec876b,
+0x705e46e5,
+0xa89316a5,
+0x35cddec8,
+0x3e1e56c9,
+0xfe4bc2f2,
+0x1103c259,
+0x82088c2a,
+0xebf55d36,
+0xfb25fcc8,
+0x486c86d5,
+0x99df8e83,
+0x6afe52ac,
+0xd4f9a1c1,
+0xad8fe42c,
+0x399ec439,
+0xc25ee67d,
+0xb4d8f6af,
+0x3ed09b66,
+0xe5c8d8f2,
+0xc1e1074f,
+0xc8bf2ab5,
+0xb0542ca7,
+0xd6d36345,
+0x4c2e85d0,
+0x80156d92,
+0x589e99c2,
+0x59634b01,
+0xa6f69080,
+0x2da5780a,
+0x3982ce4f,
+0x7305aa01,
+0xe6ef28da,
+0x3cb483f2,
+0xb4c97cea,
+0x846997ea,
+0xb6320a00,
+0xd3e14ad7,
+0x2c9b134d,
+0xebcb2cf7,
+0x303b0b4f,
+0x06b31e69,
+0xb3f4e7cf,
+0x53fb8e0a,
+0xe3ac1924,
+0x61aa87a1,
+0x6a825bf9,
+0xf78b3d01,
+0x10895912,
+0xbc5b106b,
+0x51d17b47,
+0x9c2587e4,
+0x64d28297,
+0x46a88a0a,
+0x453658b4,
+0x9238a835,
+0x29f7ea2d,
+0xb23f4bb2,
+0xd9e2c9db,
+0x35276ea7,
+0xd9c6b627,
+0xfdfa3250,
+0xeee737e2,
+0x8ea968e7,
+0x6d167474,
+0x7c5c1157,
+0x5145c31b,
+0x89ddef6b,
+0x940512f6,
+0x35e5657a,
+0x234617e1,
+0x08f2929b,
+0x1368a443,
+0xa164b848,
+0x4ccc71e4,
+0xd20a26a7,
+0x83ea362b,
+0x472a7d8e,
+0x2f5ff1f1,
+0xb32bc1ff,
+0x5273874d,
+0x6b1b87ba,
+0x08dc0f63,
+0x6a27aa75,
+0xd1a95f3c,
+0x18b8df80,
+0x6c687feb,
+0x20627a1f,
+0x9f75886d,
+0xc937baac,
+0x0d4142e6,
+0xe2d8aa26,
+0x77342228,
+0xdbd0b7a7,
+0xbd52ff86,
+0x229845d8,
+0xbc74a391,
+0x90a73293,
+0x1b589340,
+0x6c8f890b,
+0xf4f3307c,
+0x4d37e1d2,
+0x0c7f7841,
+0x02394e36,
+0x61fada47,
+0xf0574619,
+0x75aa4f33,
+0x2702f177,
+0xe855b520,
+0x40e39412,
+0xa6287bcc,
+0xf573c850,
+0x8b5ba8e9,
+0xf591a4ce,
+0x404eb036,
+0xf3a7eea6,
+0xd9f0bfb3,
+0x088344b1,
+0x0540f4aa,
+0xb2a0b2ca,
+0x2a6bf2b8,
+0xdb584476,
+0xfc41d43f,
+0x0e481ca4,
+0xd9d5fdde,
+0xdc0ca34d,
+0x3fae688e,
+0x04573cb0,
+0xaca33f74,
+0x907f6ba3,
+0x7ff6151b,
+0x7e9224cd,
+0x899c8a36,
+0xb07ad2fe,
+0xba261eac,
+0x2c2711ef,
+0x90432778,
+0x3ce50bfb,
+0x1fd14a13,
+0x87c83d2b,
+0xdbd05323,
+0x97915fa5,
+0x6fc0d350,
+0xb4ba6a7e,
+0x9f00b996,
+0x83283149,
+0x70ee16f3,
+0xfdbeb53b,
+0x54255bb8,
+0xf8e7c4bc,
+0x27da3f2c,
+0xa3adc25b,
+0xde8c3f02,
+0xef446688,
+0x32cdc858,
+0x4eb8f5f4,
+0x4a133dcf,
+0xb6edd509,
+0xf8eb14c9,
+0xa0245ed7,
+0x2290ad9f,
+0xb298a734,
+0xb8cd229a,
+0x7ec02eb8,
+0x15c4c4e9,
+0xed3013c2,
+0xc6aa19f1,
+0xd1e1c02e,
+0x02d19aef,
+0xff1b0d67,
+0x5d99fbe3,
+0x0f09e976,
+0xbe0ca285,
+0x76ec7af0,
+0x04f0a8d7,
+0x57e2602a,
+0x1011d515,
+0x7c44a6cc,
+0x410ff8a5,
+0x95717baf,
+0x12500c80,
+0x6dcfe606,
+0x4e9a25ba,
+0x83e8bef4,
+0x846c0833,
+0xa23f2b83,
+0xaa689d89,
+0xd4437152,
+0x5138bfe6,
+0x84c9398d,
+0x09f19d39,
+0x4981bc31,
+0xb9328d77,
+0xa33f0b0c,
+0x1d5d2997,
+0x685d3852,
+0x92ee6120,
+];
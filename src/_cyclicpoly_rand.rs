@@ -0,0 +1,259 @@
+
+const T : [u64; 256] = [
+0xde46bc061e7cede2,
+0x52a10c81d8cb4e95,
+0x63b28e80f422697c,
+0x2627b0cb67da4d85,
+0xe3bb2aff2f5145dc,
+0x6f9e6e791db29baf,
+0x17b0cf7a612c683a,
+0xe9c578979d918608,
+0x8c21fbb961f77334,
+0xdf4bf6f7b6e1b3ad,
+0xcebc861e38832d1c,
+0x8889476c1bb39533,
+0x8627c26e0e2aa8c3,
+0xb526908b34846c72,
+0xe564af006f58a4aa,
+0x9ca1d74ddd3f9e16,
+0xbc84cd6cd07f6544,
+0x152ae1577add2496,
+0x458ce7375a185895,
+0xfb44c9c18045331c,
+0x43a2bb43f2569e07,
+0x74e311994efd6da6,
+0x6e51c780a04bf232,
+0x5ff02a733885d9e5,
+0x91160e6884816cf3,
+0x96ded9191af01de6,
+0xe1be45cc8c6116d6,
+0x14dbf022f3b1a125,
+0xb6bce73695f6ad38,
+0x96a88a8d48afe330,
+0x3e3e6e188d9febcf,
+0x2a31c0ad81787b19,
+0xa530e81153cd444b,
+0x6f228e49c794e026,
+0x71973c50f779124a,
+0xf1e438716d2ab0da,
+0x338e541cf4529e36,
+0xc4894dd5f037423e,
+0x9068f19b76066b5f,
+0x262c62c527f7c18c,
+0x56996f41b3fe4563,
+0xf71da42ce113c1e5,
+0x70b46d46ec7768b4,
+0x196b32731079922b,
+0x9874f0f4c25231bd,
+0x19e724e701a9a436,
+0x520253617ed91f55,
+0x015a6ebf4767aa8f,
+0xf8568e9aefbc44d3,
+0x4e5f883b3b1d9e7f,
+0xf9fe5b9f3de7140c,
+0x4c646a1982cb2ce9,
+0xcb0f8a94759bd1bc,
+0x3f5d0b7423e915a4,
+0x1a501313fc9c21a9,
+0x9fd68c6b2c7d41b9,
+0xc594dbb5e4f1b853,
+0xf2d1f0d0b4fe3c00,
+0x77ccbc73bb0f762a,
+0x9150bd3d6afb8e66,
+0xf95247169694ecce,
+0x6643df5081362b5e,
+0xfce2f11e7902eb9d,
+0xf07d43a20df5cdde,
+0x67ce1f8cfe57dbfe,
+0xeaaaf7e922c1af84,
+0xa6393aa03f26777e,
+0xb950fff7e512d197,
+0x2c3eff842aedebfd,
+0x0748080ac9a5a667,
+0xac9d31c380c2d9d7,
+0xc359756c3df73219,
+0x8d18adabffe9a1c3,
+0xffbe1f54e4555761,
+0x44884ea6144386e7,
+0xb6384019651ceb71,
+0x1d1d8c5827dfb30e,
+0x241ed5cbd4b838b1,
+0xcdf1ccd8eea07138,
+0x360eebfeb24c7002,
+0xad8842b9f27be61a,
+0x572c66dfd242e3f1,
+0xdb9713d475101c60,
+0xc5fdaaa6b75897ed,
+0xb19e586f73a7f9a7,
+0x2b89d6fb7e4dd225,
+0x4eda7133fa35bb77,
+0x9159aa6f87548907,
+0xf12377cf1fb6300c,
+0xc97ba5636145421b,
+0xc368b992cc44db78,
+0xbcb86c9b429317e1,
+0xa6b45d7b4202f688,
+0x01e2fb4eb23ffa12,
+0x1d3608e879bfde30,
+0xf46aace219923443,
+0x6227584a9ca7bd90,
+0x343ba9dc6c1c1ca8,
+0x16efa85e49239f9b,
+0x9c180b1f22881c5e,
+0xafa05f1a8ebf045b,
+0xa8dc68957426f390,
+0x5d55360edcfe8bdd,
+0x8b4f55aa4c3137d2,
+0x6a029239b872e6ef,
+0x1c49cdc19305fdbb,
+0xa9af3298ac256bd5,
+0x1627d66eacfcd2ae,
+0x187eb9e6bc3ee995,
+0xa4f01c99678c00c8,
+0x1c341359ed7e1b54,
+0x385c960985e97fb0,
+0x18d9b293ae38843a,
+0x61b0ce8a53321ce3,
+0xcd211d94aa8dba7b,
+0x7e5d3769892530c9,
+0x24cea0696e963f48,
+0xb9283600500ad5fb,
+0x85216a954db61610,
+0x0049b191b046f393,
+0x73b6845aaf31573d,
+0x5bea78b4527191c0,
+0xa414532a440745a2,
+0x97d7887079370a30,
+0xe53801631a93bb87,
+0xde075ed1697d2d3e,
+0x53eac2cc772ca301,
+0x355331173820cf92,
+0x1de32be50c0079fe,
+0x7c5993ba4a132093,
+0xc845e25c486412a0,
+0xf6ba4e1b1946aa11,
+0x1a25c770bbdce0e2,
+0xa6008908d0bb9a5a,
+0x1d7ff29cfcccd284,
+0x91d3565c3e2a6e7d,
+0x6cd220c627c4c149,
+0xfb90f5b6858483c8,
+0x13d48d15bd721440,
+0x4b46b545e3daa4e8,
+0xa4c9480e678718fc,
+0x6aff454bd3fbca32,
+0x0eeb43f051df8ece,
+0xf478d7ff0802d511,
+0x502f800d432fe168,
+0x511cda82a5b9df83,
+0xf2a4d0c50638d5ca,
+0x8212bbe6f50b204f,
+0x29972a5a8654a639,
+0x1351c491d216027b,
+0xf54e60abba595730,
+0xd4646585270f3413,
+0x129167945aac4177,
+0x6c3e9c75846fd3fb,
+0x17b69d5c96cdf599,
+0xa9cfa9054ca944bf,
+0x2592eb9b96c79740,
+0xf0f8bae90e4964fb,
+0x3be2610006042d5d,
+0x49ebc3ccc619e9dd,
+0x0545103d91267d41,
+0x52b563ebe98b7e1b,
+0xbab1bd54a01ca782,
+0x17c14aa36b5031ff,
+0x2f1cd2d292cd1c6d,
+0x7ede18a9bcd4f857,
+0xca1d72d7d5439e70,
+0x274d06e8fdc8ea48,
+0x84340f64e1d2cdb0,
+0x11b173430bb6029a,
+0x6fd4c79ef69d9a9c,
+0xdbadc99c3db97b9d,
+0x538e3d894315aa7e,
+0x27b077771753504a,
+0xc0b4e3ae85d3cbaf,
+0x7a8abe212e686cb2,
+0x7c090ca919367348,
+0x538a7cc9b284e3e0,
+0x9b590d5ab3a1cecb,
+0x01bcda92f81f86a8,
+0x4c55272e039e1058,
+0xec45fc837bd29072,
+0x772f718e98422d12,
+0xf7c6442fc588123e,
+0x2d4cbaafbdc009e0,
+0x138a8aceef70e7ab,
+0xd9b91345e03116ca,
+0xcf561eef6324e339,
+0xbe4a485ac3d54899,
+0x1a5b14b7792135dd,
+0x94ee11a48b4c9928,
+0xfcdcc3ef187d563f,
+0xc4e52eb0a3c5c79e,
+0x5686b6f00bdf4a5c,
+0x9d93f8bd829c78f8,
+0x9bc6be787739b79b,
+0x1d29924eeb96fe0d,
+0xbbeca3c8c3a4f768,
+0xec547ccdeb62480d,
+0x293c0b4b68f414f3,
+0x9aecd81ccd27c41f,
+0x345fd456e965e989,
+0xf204348ebedb7423,
+0xf8c41db10dc2e15e,
+0xdb645dc04e79dfe0,
+0x3f1f8a2b6c217a63,
+0xcd3639a4947e404e,
+0xeccbd14ba4637996,
+0x5737f60c1ea95ca1,
+0xbc08cdfd334d342d,
+0x0ea41c23f313e108,
+0x318d6224431288b5,
+0x61120e464c56f5cf,
+0xf2032a227ec52455,
+0x32e6b79c007c4b1f,
+0x2f2a2c4856bd7f8c,
+0xb8fe90bd8b6cad9b,
+0xc6920b8a6b9ef5c3,
+0xa1b25f83542eab7e,
+0x21503451c7573f96,
+0x206e11ac2666baac,
+0x7e903863bf73738b,
+0x609afbe3f6c60506,
+0x68738a8638d9c5b9,
+0x47ccd37062458309,
+0xef1c243806a2b39b,
+0x1a2f13bb2894bd13,
+0x3ad2e07f780d6513,
+0xdc201b7c52ca7885,
+0xa5e2315cb171400e,
+0x19c92630a1814729,
+0xbcdab018df51cdba,
+0x293feb001163b0fc,
+0x7e6654b66d56d19a,
+0x14673d04df8cf3c7,
+0x0d184b50fc27c290,
+0xb3b0774768b2631b,
+0x0d670a1526c2500a,
+0xca7870ded3416207,
+0xd97d72d644716f53,
+0x808af50cc1205f87,
+0xee370f9a7ddb6ad7,
+0x35d591dc1c91309e,
+0x63772b547c999b0f,
+0xee6ecfe8bdac4ef1,
+0x06e3f8c4f65e48b4,
+0xd4b1b55cda1e572a,
+0x4fffaf980c96d7cb,
+0xfb8da84d451cf0f9,
+0x1a62e9113d56107e,
+0x787d76ebc879a4ab,
+0xf3b7f99a8ee9eae2,
+0x1835cca40dd4dc51,
+0x3ef66804e4f10b9d,
+0x8f78c7556a8c2e9a,
+0xa3e35c23922290cf,
+];
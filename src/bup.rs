@@ -14,33 +14,121 @@ pub const CHUNK_SIZE: u32 = 1 << CHUNK_BITS;
 /// Default chunk size used by `bup` (log2)
 pub const CHUNK_BITS: u32 = 13;
 
+/// Default number of extra digest bits per tree level used by `bup`
+/// (a fanout of 16)
+pub const FANOUT_BITS: u32 = 4;
+
+/// Most low bits of a default `Bup` digest that can all be set: the 16
+/// bits of `s2`, and those of `s1` below its largest value, `64 * (255 + 31)`
+const MAX_MASK_BITS: u32 =
+    16 + (usize::BITS - 1 - (WINDOW_SIZE * (255 + CHAR_OFFSET) + 1).leading_zeros());
+
+/// Mask for chunks of `avg_bytes` bytes on average, rounded up to a power
+/// of two
+///
+/// Bup edges are where the low bits of the digest are all set, so use it
+/// as both mask and target: `find_chunk_edge_mask(buf, mask, mask)`.
+/// Masks of 16 bits or more match somewhat more often than their size
+/// suggests; `BupWide` does better there.
+///
+/// Panics if the rounded size needs more than 30 bits: `s1` never gets
+/// large enough for wider masks to match at all.
+pub fn mask_for_average_size(avg_bytes: usize) -> Digest {
+    let bits = avg_bytes.next_power_of_two().trailing_zeros();
+    assert!(bits <= MAX_MASK_BITS);
+    (1 << bits) - 1
+}
+
 /// Rolling checksum method used by `bup`
 ///
 /// Strongly based on
 /// https://github.com/bup/bup/blob/706e8d273/lib/bup/bupsplit.c
 /// https://github.com/bup/bup/blob/706e8d273/lib/bup/bupsplit.h
 /// (a bit like https://godoc.org/camlistore.org/pkg/rollsum)
-pub struct Bup {
-    s1: usize,
-    s2: usize,
-    window: [u8; WINDOW_SIZE],
+///
+/// `W` is the window size and `O` the offset added to every byte. bup and
+/// librsync use 64 and 31, see `Bup`; with an offset of 0, the sums are
+/// those of a plain two-part Adler checksum over the window. `W` has to be
+/// at least 1, which is checked at compile time. Other parameters are built
+/// with `Default`, e.g. `RollSum::<48, 0>::default()`.
+///
+/// ```compile_fail
+/// let state = rollsum::RollSum::<0, 31>::initial_state();
+/// ```
+pub struct RollSum<const W: usize, const O: usize> {
+    state: BupState,
+    window: [u8; W],
     wofs: usize,
     chunk_bits: u32,
 }
 
-impl Default for Bup {
+/// `RollSum` with bup's window size and offset
+///
+/// ```
+/// use rollsum::{Bup, Engine};
+///
+/// let mut bup1 = Bup::default();
+/// let mut bup2 = Bup::new();
+/// bup1.roll(b"hello, world");
+/// bup2.roll(b"hello, world");
+/// assert_eq!(bup1.digest(), bup2.digest());
+/// ```
+pub type Bup = RollSum<WINDOW_SIZE, CHAR_OFFSET>;
+
+impl<const W: usize, const O: usize> Default for RollSum<W, O> {
     fn default() -> Self {
-        Bup {
-            s1: WINDOW_SIZE * CHAR_OFFSET,
-            s2: WINDOW_SIZE * (WINDOW_SIZE - 1) * CHAR_OFFSET,
-            window: [0; WINDOW_SIZE],
+        RollSum {
+            state: Self::initial_state(),
+            window: [0; W],
             wofs: 0,
             chunk_bits: CHUNK_BITS,
         }
     }
 }
 
-impl Engine for Bup {
+/// The two running sums behind the `Bup` digest
+///
+/// The window itself is not part of the state: `step` is given the byte
+/// leaving the window along with the byte entering it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BupState {
+    pub s1: usize,
+    pub s2: usize,
+}
+
+impl Default for BupState {
+    /// State of a default `Bup` engine whose window is all zeroes
+    fn default() -> Self {
+        Bup::initial_state()
+    }
+}
+
+impl BupState {
+    /// Digest of this state, as returned by `Bup::digest`
+    #[inline(always)]
+    pub fn digest(&self) -> Digest {
+        ((self.s1 as Digest) << 16) | ((self.s2 as Digest) & 0xffff)
+    }
+
+    /// Digest of this state without truncating `s2`, as returned by
+    /// `Bup::digest_wide`
+    #[inline(always)]
+    pub fn digest_wide(&self) -> u64 {
+        ((self.s1 as u64) << 32) | ((self.s2 as u64) & 0xffff_ffff)
+    }
+}
+
+/// Bup state transition: drop the oldest window byte and add a new one.
+///
+/// This is the arithmetic behind `Bup::roll_byte` with the default window
+/// size and offset, exposed as a pure function so that alternative
+/// implementations can be checked against it bit for bit.
+#[inline(always)]
+pub fn step(state: BupState, drop: u8, add: u8) -> BupState {
+    Bup::step(state, drop, add)
+}
+
+impl<const W: usize, const O: usize> Engine for RollSum<W, O> {
     type Digest = Digest;
 
     #[inline(always)]
@@ -50,26 +138,49 @@ impl Engine for Bup {
         // to skip bound checking to increase the performance
         // https://github.com/rust-lang/rfcs/issues/811
         let prevch = unsafe { *self.window.get_unchecked(self.wofs) };
-        self.add(prevch, newch);
+        self.state = Self::step(self.state, prevch, newch);
         unsafe { *self.window.get_unchecked_mut(self.wofs) = newch };
-        self.wofs = (self.wofs + 1) % WINDOW_SIZE;
+        self.wofs = (self.wofs + 1) % W;
+        self.paranoid_check_window();
+    }
+
+    #[inline(always)]
+    fn roll_byte_digest(&mut self, newch: u8) -> Digest {
+        self.roll_byte(newch);
+        self.state.digest()
+    }
+
+    fn find_chunk_edge_mask(&mut self, buf: &[u8], mask: Digest, target: Digest) -> Option<usize> {
+        for (i, &b) in buf.iter().enumerate() {
+            if self.roll_byte_digest(b) & mask == target {
+                self.reset();
+                return Some(i + 1);
+            }
+        }
+        None
     }
 
     fn roll(&mut self, buf: &[u8]) {
-        crate::roll_windowed(self, WINDOW_SIZE, buf);
+        crate::roll_windowed(self, buf);
+        self.paranoid_check_window();
+    }
+
+    fn window_size(&self) -> usize {
+        W
     }
 
     #[inline(always)]
     fn digest(&self) -> Digest {
-        ((self.s1 as Digest) << 16) | ((self.s2 as Digest) & 0xffff)
+        self.state.digest()
     }
 
     #[inline]
     fn reset(&mut self) {
-        *self = Bup {
+        *self = RollSum {
             chunk_bits: self.chunk_bits,
             ..Default::default()
-        }
+        };
+        self.paranoid_check_window();
     }
 }
 
@@ -83,6 +194,8 @@ impl Bup {
     ///
     /// `chunk_bits` is number of bits that need to match in
     /// the edge condition. `CHUNK_BITS` constant is the default.
+    ///
+    /// Panics if `chunk_bits` is 32 or more.
     pub fn new_with_chunk_bits(chunk_bits: u32) -> Self {
         assert!(chunk_bits < 32);
         Bup {
@@ -91,12 +204,140 @@ impl Bup {
         }
     }
 
+    /// Create new `SeededBup` engine that substitutes every byte through a
+    /// permutation derived from `seed`
+    ///
+    /// Without knowing the seed, edge positions cannot be predicted from
+    /// the data alone. `Bup` itself uses no substitution, and matches bup.
+    pub fn with_seed(seed: u64) -> SeededBup {
+        let mut subst = [0; 256];
+        for (i, s) in subst.iter_mut().enumerate() {
+            *s = i as u8;
+        }
+        for (n, i) in (1..subst.len()).rev().enumerate() {
+            let j = (crate::splitmix64(seed, n as u64) % (i as u64 + 1)) as usize;
+            subst.swap(i, j);
+        }
+        SeededBup {
+            bup: Bup::new(),
+            subst,
+        }
+    }
+}
+
+/// `Bup` rolling every byte through a seeded permutation first
+///
+/// Built by `Bup::with_seed`. The permutation is kept across `reset`.
+pub struct SeededBup {
+    bup: Bup,
+    subst: [u8; 256],
+}
+
+impl Engine for SeededBup {
+    type Digest = Digest;
+
+    #[inline(always)]
+    fn roll_byte(&mut self, newch: u8) {
+        self.bup.roll_byte(self.subst[newch as usize]);
+    }
+
+    fn roll(&mut self, buf: &[u8]) {
+        crate::roll_windowed(self, buf);
+    }
+
+    fn window_size(&self) -> usize {
+        self.bup.window_size()
+    }
+
+    #[inline(always)]
+    fn digest(&self) -> Digest {
+        self.bup.digest()
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.bup.reset();
+    }
+}
+
+impl SeededBup {
+    /// Find chunk edge using Bup defaults.
+    ///
+    /// See `Bup::find_chunk_edge`.
+    pub fn find_chunk_edge(&mut self, buf: &[u8]) -> Option<(usize, Digest)> {
+        let chunk_mask = (1 << self.bup.chunk_bits) - 1;
+        self.find_chunk_edge_cond(buf, |e: &Self| e.digest() & chunk_mask == chunk_mask)
+    }
+}
+
+impl<const W: usize, const O: usize> RollSum<W, O> {
+    const VALID_WINDOW: () = assert!(W > 0, "RollSum window must not be empty");
+
+    /// State of an engine whose window is all zeroes
+    pub fn initial_state() -> BupState {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_WINDOW;
+        BupState {
+            s1: W * O,
+            s2: W * (W - 1) * O,
+        }
+    }
+
+    /// State transition: drop the oldest window byte and add a new one.
+    ///
+    /// See `bup::step`. The sums wrap, so that states that no window leads
+    /// to cannot overflow either.
     #[inline(always)]
-    fn add(&mut self, drop: u8, add: u8) {
-        self.s1 += add as usize;
-        self.s1 -= drop as usize;
-        self.s2 += self.s1;
-        self.s2 -= WINDOW_SIZE * (drop as usize + CHAR_OFFSET);
+    pub fn step(state: BupState, drop: u8, add: u8) -> BupState {
+        let s1 = state
+            .s1
+            .wrapping_add(add as usize)
+            .wrapping_sub(drop as usize);
+        let s2 = state
+            .s2
+            .wrapping_add(s1)
+            .wrapping_sub(W * (drop as usize + O));
+        BupState { s1, s2 }
+    }
+
+    /// Bytes in the window, oldest first
+    ///
+    /// After every public operation these are the last `W` bytes
+    /// rolled since the last reset (preceded by zeroes if fewer were rolled),
+    /// and the sums are those of a fresh engine that rolled just these bytes.
+    fn window_in_order(&self) -> impl Iterator<Item = u8> + '_ {
+        self.window[self.wofs..]
+            .iter()
+            .chain(&self.window[..self.wofs])
+            .copied()
+    }
+
+    /// Shadow model check: recompute the sums from the stored window and
+    /// make sure they agree with the running ones.
+    fn check_window(&self) {
+        let expected = self
+            .window_in_order()
+            .fold(Self::initial_state(), |state, b| Self::step(state, 0, b));
+        assert_eq!(self.state, expected, "Bup sums out of sync with window");
+    }
+
+    /// `check_window` after every public operation, with the `paranoid`
+    /// feature only: it costs O(W) per byte.
+    #[inline(always)]
+    fn paranoid_check_window(&self) {
+        if cfg!(feature = "paranoid") {
+            self.check_window();
+        }
+    }
+
+    /// Both sums in full, `(s1 << 32) | s2`, where `digest` keeps only the
+    /// low 16 bits of `s2`
+    ///
+    /// Most bits between the two sums never change, so this is no digest to
+    /// mask for chunk edges; `BupWide` has one.
+    #[inline(always)]
+    pub fn digest_wide(&self) -> u64 {
+        self.state.digest_wide()
     }
 
     /// Find chunk edge using Bup defaults.
@@ -104,7 +345,7 @@ impl Bup {
     /// See `Engine::find_chunk_edge_cond`.
     pub fn find_chunk_edge(&mut self, buf: &[u8]) -> Option<(usize, Digest)> {
         let chunk_mask = (1 << self.chunk_bits) - 1;
-        self.find_chunk_edge_cond(buf, |e: &Bup| e.digest() & chunk_mask == chunk_mask)
+        self.find_chunk_edge_cond(buf, |e: &Self| e.digest() & chunk_mask == chunk_mask)
     }
 
     /// Counts the number of low bits set in the rollsum, assuming
@@ -128,11 +369,202 @@ impl Bup {
     }
 }
 
+/// `RollSum` with a 64-bit digest, for masks wider than bup's
+///
+/// The digest is `s2 ^ (s1 << S2_BITS)`. `s2` is a weighted sum of the
+/// window bytes, so its top bits hardly ever change; `S2_BITS` is the number
+/// of its low bits that vary evenly on random data, and `s1` covers the bits
+/// above them. The classic digest keeps 16 bits of `s2` whatever the window
+/// size: with the default window, its top bits are already skewed, and masks
+/// of 16 to 24 bits match about 25% too often.
+pub struct RollSumWide<const W: usize, const O: usize>(RollSum<W, O>);
+
+/// `RollSumWide` with bup's window size and offset
+///
+/// ```
+/// use rollsum::{BupWide, Engine};
+///
+/// let mut bup1 = BupWide::default();
+/// let mut bup2 = BupWide::new();
+/// bup1.roll(b"hello, world");
+/// bup2.roll(b"hello, world");
+/// assert_eq!(bup1.digest(), bup2.digest());
+/// ```
+pub type BupWide = RollSumWide<WINDOW_SIZE, CHAR_OFFSET>;
+
+impl<const W: usize, const O: usize> Default for RollSumWide<W, O> {
+    fn default() -> Self {
+        RollSumWide(Default::default())
+    }
+}
+
+impl<const W: usize, const O: usize> Engine for RollSumWide<W, O> {
+    type Digest = u64;
+
+    #[inline(always)]
+    fn roll_byte(&mut self, newch: u8) {
+        self.0.roll_byte(newch);
+    }
+
+    fn roll(&mut self, buf: &[u8]) {
+        self.0.roll(buf);
+    }
+
+    fn window_size(&self) -> usize {
+        W
+    }
+
+    #[inline(always)]
+    fn digest(&self) -> u64 {
+        let state = self.0.state;
+        (state.s2 as u64) ^ ((state.s1 as u64) << Self::S2_BITS)
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.0.reset();
+    }
+}
+
+impl BupWide {
+    /// Create new BupWide engine with default chunking settings
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create new BupWide engine with custom chunking settings
+    ///
+    /// `chunk_bits` is number of bits that need to match in
+    /// the edge condition. `CHUNK_BITS` constant is the default.
+    ///
+    /// Panics if `chunk_bits` is more than `MAX_CHUNK_BITS`.
+    pub fn new_with_chunk_bits(chunk_bits: u32) -> Self {
+        assert!(chunk_bits <= Self::MAX_CHUNK_BITS);
+        let mut bup = Bup::new();
+        bup.chunk_bits = chunk_bits;
+        RollSumWide(bup)
+    }
+}
+
+impl<const W: usize, const O: usize> RollSumWide<W, O> {
+    /// Low bits of `s2` that vary evenly on random data: those below its
+    /// standard deviation, about `sqrt(W^3 / 3 * 5461)`
+    const S2_BITS: u32 = (128 - (1820 * (W as u128).pow(3)).leading_zeros()) / 2;
+
+    /// Most digest bits a mask can usefully test, 23 for the default window
+    ///
+    /// `S2_BITS` plus the low bits of `s1` below its standard deviation,
+    /// about `sqrt(W * 5461)`. Masks any wider match far less often than
+    /// their size suggests, if at all.
+    pub const MAX_CHUNK_BITS: u32 = Self::S2_BITS + (128 - (5461 * W as u128).leading_zeros()) / 2;
+
+    /// Find chunk edge using BupWide defaults.
+    ///
+    /// As with `Bup`, an edge is where the low `chunk_bits` bits are all
+    /// set.
+    ///
+    /// See `Engine::find_chunk_edge_cond`.
+    pub fn find_chunk_edge(&mut self, buf: &[u8]) -> Option<(usize, u64)> {
+        let chunk_mask = (1 << self.0.chunk_bits) - 1;
+        self.find_chunk_edge_cond(buf, |e: &Self| e.digest() & chunk_mask == chunk_mask)
+    }
+}
+
+/// Splits data into chunks and assigns each one a level in a bup hashsplit
+/// tree
+///
+/// Every `fanout_bits` low digest bits set beyond the `chunk_bits` needed
+/// for an edge promote the edge one level, as in bup's `hashsplit.py`:
+/// `level = (count_bits(digest) - chunk_bits) / fanout_bits`.
+pub struct TreeSplitter {
+    bup: Bup,
+    fanout_bits: u32,
+    pending: usize,
+}
+
+impl Default for TreeSplitter {
+    fn default() -> Self {
+        TreeSplitter::new_with_bits(CHUNK_BITS, FANOUT_BITS)
+    }
+}
+
+impl TreeSplitter {
+    /// Create new TreeSplitter with bup's default settings
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create new TreeSplitter with custom chunking and fanout settings
+    ///
+    /// See `Bup::new_with_chunk_bits`.
+    ///
+    /// Panics if `chunk_bits` is 32 or more, or if `fanout_bits` is zero.
+    pub fn new_with_bits(chunk_bits: u32, fanout_bits: u32) -> Self {
+        assert!(fanout_bits > 0);
+        TreeSplitter {
+            bup: Bup::new_with_chunk_bits(chunk_bits),
+            fanout_bits,
+            pending: 0,
+        }
+    }
+
+    /// Feed `buf`, calling `f(chunk_len, level)` for every chunk it ends
+    ///
+    /// `chunk_len` counts every byte since the previous edge, including
+    /// those fed in earlier calls.
+    pub fn split<F: FnMut(usize, u32)>(&mut self, mut buf: &[u8], mut f: F) {
+        while let Some((i, digest)) = self.bup.find_chunk_edge(buf) {
+            let extra_bits = self.bup.count_bits(digest) - self.bup.chunk_bits;
+            f(self.pending + i, extra_bits / self.fanout_bits);
+            self.pending = 0;
+            buf = &buf[i..];
+        }
+        self.pending += buf.len();
+    }
+
+    /// Number of bytes fed since the last edge, i.e. the length of the
+    /// final chunk if no more data follows
+    pub fn pending(&self) -> usize {
+        self.pending
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use nanorand::{Rng, WyRand};
 
+    #[test]
+    fn mask_for_average_size() {
+        assert_eq!(super::mask_for_average_size(1), 0);
+        assert_eq!(super::mask_for_average_size(8192), 0x1fff);
+        assert_eq!(super::mask_for_average_size(5000), 0x1fff);
+        assert_eq!(super::mask_for_average_size(1 << 30), (1 << 30) - 1);
+        assert_eq!(MAX_MASK_BITS, 30);
+
+        let mut data = vec![0u8; 1 << 20];
+        WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+
+        for &avg in &[1024, 4096] {
+            let mask = super::mask_for_average_size(avg);
+            let mut bup = Bup::new();
+            let mut remaining = &data[..];
+            let mut count = 0;
+            while let Some(offset) = bup.find_chunk_edge_mask(remaining, mask, mask) {
+                remaining = &remaining[offset..];
+                count += 1;
+            }
+            let observed = (data.len() - remaining.len()) / count;
+            assert!(observed * 5 > avg * 4 && observed * 5 < avg * 6);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn mask_for_average_size_beyond_max() {
+        super::mask_for_average_size((1 << 30) + 1);
+    }
+
     #[test]
     fn bup_selftest() {
         use super::Bup;
@@ -165,6 +597,236 @@ mod tests {
         assert_eq!(sum3a, sum3b);
     }
 
+    #[test]
+    fn step_same_as_roll_byte() {
+        let mut data = vec![0u8; 4096];
+        WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+
+        let mut bup = Bup::new();
+        let mut state = BupState::default();
+        let mut window = [0u8; WINDOW_SIZE];
+        for (i, &b) in data.iter().enumerate() {
+            bup.roll_byte(b);
+            state = step(state, window[i % WINDOW_SIZE], b);
+            window[i % WINDOW_SIZE] = b;
+            assert_eq!(bup.digest(), state.digest());
+        }
+    }
+
+    #[test]
+    fn window_matches_input_after_interleaving() {
+        let mut data = vec![0u8; 8192];
+        WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+
+        // Each operation consumes a prefix of its input, and reports how many
+        // bytes it used and whether the engine was reset afterwards.
+        type Op = fn(&mut Bup, &[u8]) -> (usize, bool);
+        let ops: [Op; 4] = [
+            |e, d| {
+                e.roll(&d[..10]);
+                (10, false)
+            },
+            |e, d| {
+                e.roll(&d[..1000]);
+                (1000, false)
+            },
+            |e, d| {
+                assert_eq!(e.find_chunk_edge_cond(&d[..1000], |_| false), None);
+                (1000, false)
+            },
+            |e, d| (e.find_chunk_edge_cond(d, |_| true).unwrap().0, true),
+        ];
+
+        for first in &ops {
+            for second in &ops {
+                let mut bup = Bup::new();
+                let (mut pos, mut start) = (0, 0);
+                for op in &[first, second] {
+                    let (used, reset) = op(&mut bup, &data[pos..]);
+                    bup.check_window();
+                    pos += used;
+                    if reset {
+                        start = pos;
+                    }
+                }
+
+                let mut reference = Bup::new();
+                data[start..pos]
+                    .iter()
+                    .for_each(|&b| reference.roll_byte(b));
+                assert!(bup.window_in_order().eq(reference.window_in_order()));
+                assert_eq!(bup.state, reference.state);
+
+                for &b in &data[pos..pos + 100] {
+                    bup.roll_byte(b);
+                    reference.roll_byte(b);
+                    assert_eq!(bup.digest(), reference.digest());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn zero_offset_is_plain_adler() {
+        fn check<const W: usize>() {
+            let mut data = vec![0u8; 4 * W + 100];
+            WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+
+            let mut bup = RollSum::<W, 0>::default();
+            for (i, &b) in data.iter().enumerate() {
+                bup.roll_byte(b);
+                let window = &data[(i + 1).saturating_sub(W)..=i];
+                let (a, b) = window.iter().fold((0u32, 0u32), |(a, b), &x| {
+                    let a = a.wrapping_add(x as u32);
+                    (a, b.wrapping_add(a))
+                });
+                assert_eq!(bup.digest(), (a << 16) | (b & 0xffff));
+            }
+        }
+
+        check::<64>();
+        check::<1>();
+        check::<48>();
+    }
+
+    #[test]
+    fn tree_splitter_levels() {
+        const CHUNK_BITS: u32 = 8;
+        const FANOUT_BITS: u32 = 2;
+
+        let mut data = vec![0u8; 256 * 1024];
+        WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+
+        // bupsplit_find_ofs and hashsplit.py, one byte at a time
+        let mut expected = vec![];
+        let mut bup = Bup::new();
+        let mut len = 0;
+        for &b in &data {
+            bup.roll_byte(b);
+            len += 1;
+            let mask = (1 << CHUNK_BITS) - 1;
+            if bup.digest() & mask == mask {
+                let mut rsum = bup.digest() >> CHUNK_BITS;
+                let mut bits = CHUNK_BITS;
+                loop {
+                    rsum >>= 1;
+                    if rsum & 1 == 0 {
+                        break;
+                    }
+                    bits += 1;
+                }
+                expected.push((len, (bits - CHUNK_BITS) / FANOUT_BITS));
+                bup = Bup::new();
+                len = 0;
+            }
+        }
+        assert!(expected.iter().any(|&(_, level)| level >= 2));
+
+        let mut splitter = TreeSplitter::new_with_bits(CHUNK_BITS, FANOUT_BITS);
+        let mut splits = vec![];
+        for piece in data.chunks(1000) {
+            splitter.split(piece, |len, level| splits.push((len, level)));
+        }
+        assert_eq!(splits, expected);
+        assert_eq!(splitter.pending(), len);
+    }
+
+    #[test]
+    fn seed_changes_edges() {
+        let mut data = vec![0u8; 128 * 1024];
+        WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+
+        /// Offsets of all edges in `data`
+        fn edges<E: Engine<Digest = Digest>>(mut bup: E, data: &[u8]) -> Vec<usize> {
+            let mut edges = vec![];
+            let mut pos = 0;
+            while let Some((i, _)) =
+                bup.find_chunk_edge_cond(&data[pos..], |e| e.digest() & 0xff == 0xff)
+            {
+                pos += i;
+                edges.push(pos);
+            }
+            edges
+        }
+
+        let edges1 = edges(Bup::with_seed(1), &data);
+        assert_eq!(edges1, edges(Bup::with_seed(1), &data));
+        assert!(edges1.len() > 100);
+
+        // Edges after a reset are those of a fresh engine
+        let edges2 = edges(Bup::with_seed(2), &data);
+        let mut bup = Bup::with_seed(2);
+        bup.roll(&data[..1000]);
+        bup.reset();
+        assert_eq!(edges2, edges(bup, &data));
+
+        for other in &[edges2, edges(Bup::new(), &data)] {
+            let common = edges1.iter().filter(|e| other.contains(e)).count();
+            assert!(common < edges1.len() / 4);
+        }
+    }
+
+    #[test]
+    fn wide_digest() {
+        let mut data = vec![0u8; 4096];
+        WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+
+        let mut bup = Bup::new();
+        let mut wide = BupWide::new();
+        for &b in &data {
+            bup.roll_byte(b);
+            wide.roll_byte(b);
+            let (s1, s2) = (bup.digest_wide() >> 32, bup.digest_wide() & 0xffff_ffff);
+            assert_eq!(s1 as u16, (bup.digest() >> 16) as u16);
+            assert_eq!(s2 as u16, bup.digest() as u16);
+            assert_eq!(wide.digest(), s2 ^ (s1 << 14));
+        }
+
+        assert_eq!(RollSumWide::<64, 31>::S2_BITS, 14);
+        assert_eq!(RollSumWide::<64, 31>::MAX_CHUNK_BITS, 23);
+        assert_eq!(RollSumWide::<16, 31>::MAX_CHUNK_BITS, 19);
+        assert_eq!(RollSumWide::<4095, 31>::MAX_CHUNK_BITS, 35);
+        BupWide::new_with_chunk_bits(23);
+    }
+
+    #[test]
+    #[should_panic]
+    fn wide_chunk_bits_beyond_max() {
+        BupWide::new_with_chunk_bits(24);
+    }
+
+    // Rolls 32 MiB to see enough 17-bit matches; run with `--ignored`
+    #[test]
+    #[ignore]
+    fn wide_digest_evenly_distributed() {
+        let mut data = vec![0u8; 32 << 20];
+        WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+
+        // Count how often masks of 16 and 17 low bits match, which random
+        // digests do once every 2^16 and 2^17 bytes
+        let mut wide = BupWide::new();
+        let mut matches = [[0; 2]; 2];
+        for &b in &data {
+            wide.roll_byte(b);
+            for (i, bits) in [16, 17].iter().enumerate() {
+                let mask = (1 << bits) - 1;
+                if wide.0.digest() & mask == mask {
+                    matches[0][i] += 1;
+                }
+                if wide.digest() & mask as u64 == mask as u64 {
+                    matches[1][i] += 1;
+                }
+            }
+        }
+
+        for (i, bits) in [16, 17].iter().enumerate() {
+            let expected = data.len() >> bits;
+            let [bup, wide] = [matches[0][i], matches[1][i]];
+            assert!(bup * 100 > expected * 115, "{} vs {}", bup, expected);
+            assert!(wide * 100 > expected * 90 && wide * 100 < expected * 110);
+        }
+    }
+
     #[test]
     fn count_bits() {
         let bup = Bup::new_with_chunk_bits(1);
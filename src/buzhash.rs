@@ -0,0 +1,141 @@
+use crate::cyclicpoly::CyclicHash;
+
+pub type Digest = u32;
+
+/// Default chunk size used by `buzhash`
+pub const CHUNK_SIZE: u32 = 1 << CHUNK_BITS;
+
+/// Default chunk size used by `buzhash` (log2)
+pub const CHUNK_BITS: u32 = 13;
+
+/// Default window size used by `buzhash`
+pub const WINDOW_SIZE: usize = 64;
+
+include!("_buzhash_rand.rs");
+
+/// Cyclic polynomial rolling hash ("buzhash"), the construction borg and
+/// attic use
+///
+/// The 32-bit `CyclicHash`: the digest of a window `b[0..W]` (oldest byte
+/// first) is the xor of `table[b[i]].rotate_left((W - 1 - i) % 32)`,
+/// computed the same way as
+/// https://github.com/borgbackup/borg/blob/master/src/borg/_chunker.c
+///
+/// The built-in table is this crate's own, not borg's `table_base`, so the
+/// default engine does not find borg's split points. To reproduce those,
+/// pass borg's `table_base` and the repository's seed to `with_table`, and
+/// use `CyclicHash::<u32, 4095>` (borg's default window). The tests compare with a
+/// port of borg's `buzhash()`, not with digests produced by borg itself.
+pub type Buzhash = CyclicHash<Digest, WINDOW_SIZE>;
+
+impl<const W: usize> Default for CyclicHash<Digest, W> {
+    fn default() -> Self {
+        Self::with_table(&T, 0)
+    }
+}
+
+impl Buzhash {
+    /// Create new Buzhash engine with default chunking settings
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create new Buzhash engine with custom chunking settings
+    ///
+    /// `chunk_bits` is number of bits that need to match in
+    /// the edge condition. `CHUNK_BITS` constant is the default.
+    ///
+    /// Panics if `chunk_bits` is 32 or more.
+    pub fn new_with_chunk_bits(chunk_bits: u32) -> Self {
+        Self::with_chunk_bits(chunk_bits)
+    }
+}
+
+impl<const W: usize> CyclicHash<Digest, W> {
+    /// Create new Buzhash engine using the built-in table with `seed`
+    /// xored into every entry
+    ///
+    /// This is not borg's table; see `with_table` for that.
+    ///
+    /// As in borg, this only xors a constant into every full-window digest,
+    /// and has no effect at all when `W` is a multiple of 64. It changes
+    /// where edges fall, but does not hide the table.
+    pub fn with_seed(seed: Digest) -> Self {
+        Self::with_table(&T, seed)
+    }
+
+    /// Create new Buzhash engine using `table` with `seed` xored into
+    /// every entry
+    ///
+    /// With borg's `table_base` and a repository's chunker seed, this
+    /// hashes as borg does.
+    ///
+    /// Panics if `W` is zero.
+    pub fn with_table(table: &[Digest; 256], seed: Digest) -> Self {
+        let mut seeded = *table;
+        seeded.iter_mut().for_each(|t| *t ^= seed);
+        Self::from_table(seeded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Engine;
+    use nanorand::{Rng, WyRand};
+
+    /// borg's non-rolling `buzhash()`
+    fn reference(data: &[u8], table: &[Digest; 256]) -> Digest {
+        let (last, rest) = data.split_last().unwrap();
+        let mut sum = 0;
+        for (i, &b) in rest.iter().enumerate() {
+            let imod = ((data.len() - 1 - i) & 0x1f) as u32;
+            sum ^= table[b as usize].rotate_left(imod);
+        }
+        sum ^ table[*last as usize]
+    }
+
+    fn check_against_reference<const W: usize>(seed: Digest) {
+        let mut table = T;
+        table.iter_mut().for_each(|t| *t ^= seed);
+        crate::tests::check_against_reference(CyclicHash::<u32, W>::with_seed(seed), |window| {
+            reference(window, &table)
+        });
+    }
+
+    #[test]
+    fn same_as_reference() {
+        check_against_reference::<64>(0);
+        check_against_reference::<64>(0xdeadbeef);
+        check_against_reference::<48>(0);
+        check_against_reference::<31>(0x12345678);
+        check_against_reference::<32>(0);
+        check_against_reference::<1>(0);
+        check_against_reference::<4095>(0x12345678);
+    }
+
+    #[test]
+    fn zero_window_digest() {
+        let buzhash = Buzhash::new();
+        assert_eq!(buzhash.digest(), reference(&[0; WINDOW_SIZE], &T));
+    }
+
+    #[test]
+    fn seed_changes_digest() {
+        let mut data = vec![0u8; 1024];
+        WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+
+        let mut buzhash1 = CyclicHash::<u32, 4095>::with_seed(1);
+        let mut buzhash2 = CyclicHash::<u32, 4095>::with_seed(2);
+        buzhash1.roll(&data);
+        buzhash2.roll(&data);
+        assert_ne!(buzhash1.digest(), buzhash2.digest());
+
+        // Every rotation of the seed cancels out over 64 window positions
+        let mut buzhash1 = Buzhash::with_seed(1);
+        let mut buzhash2 = Buzhash::with_seed(2);
+        buzhash1.roll(&data);
+        buzhash2.roll(&data);
+        assert_eq!(buzhash1.digest(), buzhash2.digest());
+    }
+}
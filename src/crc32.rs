@@ -0,0 +1,336 @@
+use super::Engine;
+use std::default::Default;
+
+pub type Digest = u32;
+
+/// Default chunk size used by `crc32`
+pub const CHUNK_SIZE: u32 = 1 << CHUNK_BITS;
+
+/// Default chunk size used by `crc32` (log2)
+pub const CHUNK_BITS: u32 = 13;
+
+/// Default window size used by `crc32`
+pub const WINDOW_SIZE: usize = 64;
+
+/// CRC-32 polynomial used by zlib, Ethernet and PNG (reflected)
+pub const IEEE: u32 = 0xedb8_8320;
+
+/// CRC-32C polynomial used by iSCSI and ext4 (reflected)
+pub const CASTAGNOLI: u32 = 0x82f6_3b78;
+
+/// CRC-32 over a sliding window
+///
+/// The digest is the standard CRC-32 (initial value and final xor of
+/// `0xffffffff`) of the last `W` bytes. Bytes leave the window through a
+/// precomputed table of their contribution `W` positions later.
+///
+/// With the `crc32-hw` feature, `roll` and chunk edge searches over CRC-32C
+/// use the SSE 4.2 `crc32` instruction on x86_64 CPUs that support it. The
+/// CPU is checked once per engine, and the loop is picked once per call.
+/// The digests are the same either way.
+pub struct RollingCrc32<const W: usize = WINDOW_SIZE> {
+    crc: u32,
+    initial_crc: u32,
+    table: [u32; 256],
+    out_table: [u32; 256],
+    window: [u8; W],
+    wofs: usize,
+    #[cfg(all(feature = "crc32-hw", target_arch = "x86_64"))]
+    hw: bool,
+    chunk_bits: u32,
+}
+
+impl<const W: usize> Default for RollingCrc32<W> {
+    fn default() -> Self {
+        Self::with_polynomial(IEEE)
+    }
+}
+
+impl<const W: usize> Engine for RollingCrc32<W> {
+    type Digest = Digest;
+
+    #[inline(always)]
+    fn roll_byte(&mut self, newch: u8) {
+        #[cfg(all(feature = "crc32-hw", target_arch = "x86_64"))]
+        {
+            if self.hw {
+                // Only set after the instruction was detected
+                return unsafe { hw::step(self, newch) };
+            }
+        }
+        self.step(newch, update);
+    }
+
+    fn roll(&mut self, buf: &[u8]) {
+        let start = buf.len().saturating_sub(W);
+        self.scan(&buf[start..], |_| false);
+    }
+
+    fn window_size(&self) -> usize {
+        W
+    }
+
+    #[inline(always)]
+    fn digest(&self) -> Digest {
+        !self.crc
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.crc = self.initial_crc;
+        self.window = [0; W];
+        self.wofs = 0;
+    }
+
+    fn find_chunk_edge_cond<F>(&mut self, buf: &[u8], cond: F) -> Option<(usize, Digest)>
+    where
+        F: Fn(&Self) -> bool,
+    {
+        let offset = self.scan(buf, cond)?;
+        let digest = self.digest();
+        self.reset();
+        Some((offset, digest))
+    }
+}
+
+impl RollingCrc32 {
+    /// Create new RollingCrc32 engine with default chunking settings
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create new RollingCrc32 engine with custom chunking settings
+    ///
+    /// `chunk_bits` is number of bits that need to match in
+    /// the edge condition. `CHUNK_BITS` constant is the default.
+    ///
+    /// Panics if `chunk_bits` is 32 or more.
+    pub fn new_with_chunk_bits(chunk_bits: u32) -> Self {
+        Self::with_chunk_bits(chunk_bits)
+    }
+}
+
+impl<const W: usize> RollingCrc32<W> {
+    /// Create new RollingCrc32 engine of any window size with custom chunking
+    /// settings
+    ///
+    /// See `RollingCrc32::new_with_chunk_bits`.
+    pub fn with_chunk_bits(chunk_bits: u32) -> Self {
+        assert!(chunk_bits < 32);
+        RollingCrc32 {
+            chunk_bits,
+            ..Default::default()
+        }
+    }
+
+    /// Create new RollingCrc32 engine using the reflected polynomial `pol`,
+    /// e.g. `IEEE` or `CASTAGNOLI`
+    ///
+    /// Panics if `W` is zero.
+    pub fn with_polynomial(pol: u32) -> Self {
+        assert!(W > 0);
+        let mut table = [0; 256];
+        for (b, t) in table.iter_mut().enumerate() {
+            *t = (0..8).fold(b as u32, |crc, _| {
+                (crc >> 1) ^ (pol & 0u32.wrapping_sub(crc & 1))
+            });
+        }
+
+        let mut crc32 = RollingCrc32 {
+            crc: !0,
+            initial_crc: !0,
+            table,
+            out_table: [0; 256],
+            window: [0; W],
+            wofs: 0,
+            #[cfg(all(feature = "crc32-hw", target_arch = "x86_64"))]
+            hw: pol == CASTAGNOLI && is_x86_feature_detected!("sse4.2"),
+            chunk_bits: CHUNK_BITS,
+        };
+
+        // Rolling `b` in and `W` more bytes after it leaves the contribution
+        // of `b`, plus one extra step applied to the initial value. Both are
+        // undone by xoring in the difference from rolling just the `W` bytes.
+        // The contribution of `b` is linear, so only single-bit bytes are
+        // rolled directly.
+        let zeroes = (0..W).fold(!0, |crc, _| update(&crc32.table, crc, 0));
+        let out = |b: u8| {
+            (0..W).fold(update(&crc32.table, !0, b), |crc, _| {
+                update(&crc32.table, crc, 0)
+            }) ^ zeroes
+        };
+        let out_zero = out(0);
+        let out_bits: Vec<u32> = (0..8).map(|bit| out(1 << bit) ^ out_zero).collect();
+        for (b, o) in crc32.out_table.iter_mut().enumerate() {
+            *o = (0..8)
+                .filter(|bit| b & (1 << bit) != 0)
+                .fold(out_zero, |o, bit| o ^ out_bits[bit]);
+        }
+        crc32.initial_crc = zeroes;
+        crc32.crc = zeroes;
+        crc32
+    }
+
+    /// Find chunk edge using RollingCrc32 defaults.
+    ///
+    /// An edge is where the low `chunk_bits` bits are all zero.
+    ///
+    /// See `Engine::find_chunk_edge_cond`.
+    pub fn find_chunk_edge(&mut self, buf: &[u8]) -> Option<(usize, Digest)> {
+        let chunk_mask = (1 << self.chunk_bits) - 1;
+        self.find_chunk_edge_cond(buf, |e: &Self| e.digest() & chunk_mask == 0)
+    }
+
+    /// Roll `newch` into the window, feeding it to the CRC with `update`
+    #[inline(always)]
+    fn step<U>(&mut self, newch: u8, update: U)
+    where
+        U: Fn(&[u32; 256], u32, u8) -> u32,
+    {
+        // See `Bup::roll_byte`: `wofs` is always less than `W`
+        let prevch = unsafe { *self.window.get_unchecked(self.wofs) };
+        self.crc = update(&self.table, self.crc, newch) ^ self.out_table[prevch as usize];
+        unsafe { *self.window.get_unchecked_mut(self.wofs) = newch };
+        self.wofs = (self.wofs + 1) % W;
+    }
+
+    /// Roll bytes of `buf` until `stop` holds, returning the offset of the
+    /// first byte not rolled
+    #[inline(always)]
+    fn scan_with<U, F>(&mut self, buf: &[u8], update: U, stop: F) -> Option<usize>
+    where
+        U: Fn(&[u32; 256], u32, u8) -> u32,
+        F: Fn(&Self) -> bool,
+    {
+        for (i, &b) in buf.iter().enumerate() {
+            self.step(b, &update);
+            if stop(self) {
+                return Some(i + 1);
+            }
+        }
+        None
+    }
+
+    /// `scan_with` using the fastest `update` available
+    #[inline]
+    fn scan<F>(&mut self, buf: &[u8], stop: F) -> Option<usize>
+    where
+        F: Fn(&Self) -> bool,
+    {
+        #[cfg(all(feature = "crc32-hw", target_arch = "x86_64"))]
+        {
+            if self.hw {
+                // Only set after the instruction was detected
+                return unsafe { hw::scan(self, buf, stop) };
+            }
+        }
+        self.scan_with(buf, update, stop)
+    }
+}
+
+/// CRC register after feeding `byte` into `crc`, using `table`
+#[inline(always)]
+fn update(table: &[u32; 256], crc: u32, byte: u8) -> u32 {
+    (crc >> 8) ^ table[((crc ^ byte as u32) & 0xff) as usize]
+}
+
+#[cfg(all(feature = "crc32-hw", target_arch = "x86_64"))]
+mod hw {
+    use super::RollingCrc32;
+    use std::arch::x86_64::_mm_crc32_u8;
+
+    /// `RollingCrc32::step` compiled for SSE 4.2
+    #[target_feature(enable = "sse4.2")]
+    pub unsafe fn step<const W: usize>(crc32: &mut RollingCrc32<W>, newch: u8) {
+        crc32.step(newch, |_, crc, byte| _mm_crc32_u8(crc, byte))
+    }
+
+    /// `RollingCrc32::scan_with` compiled for SSE 4.2, so the `crc32`
+    /// instruction is inlined into the loop
+    #[target_feature(enable = "sse4.2")]
+    pub unsafe fn scan<F, const W: usize>(
+        crc32: &mut RollingCrc32<W>,
+        buf: &[u8],
+        stop: F,
+    ) -> Option<usize>
+    where
+        F: Fn(&RollingCrc32<W>) -> bool,
+    {
+        crc32.scan_with(buf, |_, crc, byte| _mm_crc32_u8(crc, byte), stop)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bitwise CRC-32 of `data`
+    fn reference(data: &[u8], pol: u32) -> Digest {
+        let crc = data.iter().fold(!0u32, |crc, &b| {
+            (0..8).fold(crc ^ b as u32, |crc, _| {
+                if crc & 1 != 0 {
+                    (crc >> 1) ^ pol
+                } else {
+                    crc >> 1
+                }
+            })
+        });
+        !crc
+    }
+
+    fn check_against_reference<const W: usize>(pol: u32) {
+        let crc32 = RollingCrc32::<W>::with_polynomial(pol);
+        assert_eq!(crc32.digest(), reference(&[0; W], pol));
+        crate::tests::check_against_reference(crc32, |window| reference(window, pol));
+    }
+
+    #[test]
+    fn same_as_reference() {
+        check_against_reference::<64>(IEEE);
+        check_against_reference::<1>(IEEE);
+        check_against_reference::<48>(IEEE);
+        check_against_reference::<64>(CASTAGNOLI);
+        check_against_reference::<255>(CASTAGNOLI);
+    }
+
+    #[test]
+    fn check_values() {
+        let mut crc32 = RollingCrc32::<9>::with_polynomial(IEEE);
+        crc32.roll(b"123456789");
+        assert_eq!(crc32.digest(), 0xcbf4_3926);
+
+        let mut crc32 = RollingCrc32::<9>::with_polynomial(CASTAGNOLI);
+        crc32.roll(b"123456789");
+        assert_eq!(crc32.digest(), 0xe306_9283);
+    }
+
+    #[cfg(all(feature = "crc32-hw", target_arch = "x86_64"))]
+    #[test]
+    fn hw_same_as_table() {
+        use nanorand::{Rng, WyRand};
+
+        let mut data = vec![0u8; 4096];
+        WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+
+        let mut crc32 = <RollingCrc32>::with_polynomial(CASTAGNOLI);
+        let mut table_only = <RollingCrc32>::with_polynomial(CASTAGNOLI);
+        assert_eq!(crc32.hw, is_x86_feature_detected!("sse4.2"));
+        table_only.hw = false;
+
+        crc32.roll(&data);
+        table_only.roll(&data);
+        assert_eq!(crc32.digest(), table_only.digest());
+
+        for chunk_bits in 0..8 {
+            let cond = |e: &RollingCrc32| e.digest() & ((1 << chunk_bits) - 1) == 0;
+            let mut offset = 0;
+            while let Some((i, digest)) = crc32.find_chunk_edge_cond(&data[offset..], cond) {
+                let table_edge = table_only.find_chunk_edge_cond(&data[offset..], cond);
+                assert_eq!(table_edge, Some((i, digest)));
+                offset += i;
+            }
+            assert_eq!(table_only.find_chunk_edge_cond(&data[offset..], cond), None);
+            assert_eq!(crc32.digest(), table_only.digest());
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use super::Engine;
+use std::default::Default;
+use std::ops::{BitAnd, BitXor};
+
+pub type Digest = u64;
+
+/// Default chunk size used by `cyclicpoly`
+pub const CHUNK_SIZE: u32 = 1 << CHUNK_BITS;
+
+/// Default chunk size used by `cyclicpoly` (log2)
+pub const CHUNK_BITS: u32 = 13;
+
+/// Default window size used by `cyclicpoly`
+pub const WINDOW_SIZE: usize = 64;
+
+include!("_cyclicpoly_rand.rs");
+
+/// Integer types a `CyclicHash` digest can be made of
+pub trait Word: Copy + PartialEq + BitAnd<Output = Self> + BitXor<Output = Self> {
+    const BITS: u32;
+    const ZERO: Self;
+
+    fn rotate_left(self, n: u32) -> Self;
+
+    /// Mask of the low `n` bits, for `n` less than `BITS`
+    fn low_bits(n: u32) -> Self;
+}
+
+impl Word for u32 {
+    const BITS: u32 = 32;
+    const ZERO: Self = 0;
+
+    #[inline(always)]
+    fn rotate_left(self, n: u32) -> Self {
+        self.rotate_left(n)
+    }
+
+    fn low_bits(n: u32) -> Self {
+        (1 << n) - 1
+    }
+}
+
+impl Word for u64 {
+    const BITS: u32 = 64;
+    const ZERO: Self = 0;
+
+    #[inline(always)]
+    fn rotate_left(self, n: u32) -> Self {
+        self.rotate_left(n)
+    }
+
+    fn low_bits(n: u32) -> Self {
+        (1 << n) - 1
+    }
+}
+
+/// Rolling hash by cyclic polynomial (hashing by cycling), with a digest of
+/// type `D`
+///
+/// The digest of a window `b[0..W]` (oldest byte first) is the xor of
+/// `table[b[i]].rotate_left((W - 1 - i) % D::BITS)`. Unlike `Gear`, bytes
+/// leave the window explicitly, so the digest depends on exactly the last
+/// `W` bytes.
+///
+/// See Cohen, "Recursive Hashing Functions for n-Grams" (1997). `CyclicPoly`
+/// is the 64-bit version, and `Buzhash` the 32-bit one, borg's construction.
+pub struct CyclicHash<D: Word = Digest, const W: usize = WINDOW_SIZE> {
+    digest: D,
+    initial_digest: D,
+    table: [D; 256],
+    /// `table[b].rotate_left(W % D::BITS)`, the contribution of `b` leaving
+    /// the window
+    out_table: [D; 256],
+    window: [u8; W],
+    wofs: usize,
+    chunk_bits: u32,
+}
+
+/// 64-bit `CyclicHash` over the default window
+///
+/// Other windows are built from `CyclicHash`, e.g.
+/// `CyclicHash::<u64, 48>::with_seed(seed)`.
+pub type CyclicPoly = CyclicHash<Digest, WINDOW_SIZE>;
+
+impl<const W: usize> Default for CyclicHash<Digest, W> {
+    fn default() -> Self {
+        Self::with_table(T)
+    }
+}
+
+impl<D: Word, const W: usize> Engine for CyclicHash<D, W> {
+    type Digest = D;
+
+    #[inline(always)]
+    fn roll_byte(&mut self, newch: u8) {
+        // See `Bup::roll_byte`: `wofs` is always less than `W`
+        let prevch = unsafe { *self.window.get_unchecked(self.wofs) };
+        self.digest = self.digest.rotate_left(1)
+            ^ self.out_table[prevch as usize]
+            ^ self.table[newch as usize];
+        unsafe { *self.window.get_unchecked_mut(self.wofs) = newch };
+        self.wofs = (self.wofs + 1) % W;
+    }
+
+    fn roll(&mut self, buf: &[u8]) {
+        crate::roll_windowed(self, buf);
+    }
+
+    fn window_size(&self) -> usize {
+        W
+    }
+
+    #[inline(always)]
+    fn digest(&self) -> D {
+        self.digest
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.digest = self.initial_digest;
+        self.window = [0; W];
+        self.wofs = 0;
+    }
+}
+
+impl CyclicPoly {
+    /// Create new CyclicPoly engine with default chunking settings
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create new CyclicPoly engine with custom chunking settings
+    ///
+    /// `chunk_bits` is number of bits that need to match in
+    /// the edge condition. `CHUNK_BITS` constant is the default.
+    ///
+    /// Panics if `chunk_bits` is 64 or more.
+    pub fn new_with_chunk_bits(chunk_bits: u32) -> Self {
+        Self::with_chunk_bits(chunk_bits)
+    }
+}
+
+impl<D: Word, const W: usize> CyclicHash<D, W>
+where
+    Self: Default,
+{
+    /// Create new engine of any digest type and window size with custom
+    /// chunking settings
+    ///
+    /// See `CyclicPoly::new_with_chunk_bits`; the default settings are
+    /// those of `Default`.
+    ///
+    /// Panics if `chunk_bits` is `D::BITS` or more.
+    pub fn with_chunk_bits(chunk_bits: u32) -> Self {
+        assert!(chunk_bits < D::BITS);
+        CyclicHash {
+            chunk_bits,
+            ..Default::default()
+        }
+    }
+}
+
+impl<D: Word, const W: usize> CyclicHash<D, W> {
+    /// Engine using `table` as is
+    ///
+    /// Panics if `W` is zero.
+    pub(crate) fn from_table(table: [D; 256]) -> Self {
+        assert!(W > 0);
+        let mut out_table = table;
+        for o in out_table.iter_mut() {
+            *o = o.rotate_left((W % D::BITS as usize) as u32);
+        }
+        // The state of an engine whose window is all zeroes
+        let initial_digest = (0..W).fold(D::ZERO, |digest, i| {
+            digest ^ table[0].rotate_left((i % D::BITS as usize) as u32)
+        });
+        CyclicHash {
+            digest: initial_digest,
+            initial_digest,
+            table,
+            out_table,
+            window: [0; W],
+            wofs: 0,
+            chunk_bits: CHUNK_BITS,
+        }
+    }
+
+    /// Find chunk edge using default settings.
+    ///
+    /// An edge is where the low `chunk_bits` bits are all zero, as in borg.
+    ///
+    /// See `Engine::find_chunk_edge_cond`.
+    pub fn find_chunk_edge(&mut self, buf: &[u8]) -> Option<(usize, D)> {
+        let chunk_mask = D::low_bits(self.chunk_bits);
+        self.find_chunk_edge_cond(buf, |e: &Self| e.digest() & chunk_mask == D::ZERO)
+    }
+}
+
+impl<const W: usize> CyclicHash<Digest, W> {
+    /// Create new CyclicPoly engine with a table derived from `seed`
+    ///
+    /// Without knowing the seed, edge positions cannot be predicted from
+    /// the data alone.
+    pub fn with_seed(seed: u64) -> Self {
+        let mut table = [0; 256];
+        for (n, t) in table.iter_mut().enumerate() {
+            *t = crate::splitmix64(seed, n as u64);
+        }
+        Self::with_table(table)
+    }
+
+    /// Create new CyclicPoly engine using `table`
+    ///
+    /// Panics if `W` is zero.
+    pub fn with_table(table: [Digest; 256]) -> Self {
+        Self::from_table(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanorand::{Rng, WyRand};
+
+    fn reference<D: Word>(data: &[u8], table: &[D; 256]) -> D {
+        data.iter().enumerate().fold(D::ZERO, |digest, (i, &b)| {
+            let rotation = ((data.len() - 1 - i) % D::BITS as usize) as u32;
+            digest ^ table[b as usize].rotate_left(rotation)
+        })
+    }
+
+    fn check_against_reference<D: Word + std::fmt::Debug, const W: usize>(
+        engine: CyclicHash<D, W>,
+    ) {
+        let table = engine.table;
+        crate::tests::check_against_reference(engine, |window| reference(window, &table));
+    }
+
+    #[test]
+    fn same_as_reference() {
+        check_against_reference(CyclicPoly::new());
+        check_against_reference(CyclicHash::<u64, 1>::default());
+        check_against_reference(CyclicHash::<u64, 48>::default());
+        check_against_reference(CyclicHash::<u64, 100>::with_seed(1));
+
+        let mut table = [0u32; 256];
+        table.iter_mut().zip(&T).for_each(|(t, &u)| *t = u as u32);
+        check_against_reference(CyclicHash::<u32, 48>::from_table(table));
+        check_against_reference(CyclicHash::<u32, 100>::from_table(table));
+    }
+
+    #[test]
+    fn seed_changes_digest() {
+        let mut data = vec![0u8; 1024];
+        WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+
+        let mut engine1 = CyclicPoly::with_seed(1);
+        let mut engine2 = CyclicPoly::with_seed(1);
+        let mut engine3 = CyclicPoly::with_seed(2);
+        engine1.roll(&data);
+        engine2.roll(&data);
+        engine3.roll(&data);
+        assert_eq!(engine1.digest(), engine2.digest());
+        assert_ne!(engine1.digest(), engine3.digest());
+    }
+}
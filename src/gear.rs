@@ -1,7 +1,6 @@
 use super::Engine;
 use std::default::Default;
 use std::mem;
-use std::num::Wrapping;
 
 pub type Digest = u64;
 
@@ -11,18 +10,76 @@ pub const CHUNK_SIZE: u32 = 1 << CHUNK_BITS;
 /// Default chunk size used by `gear` (log2)
 pub const CHUNK_BITS: u32 = 13;
 
-/// The effective window size used by `gear`
+/// The effective window size used by `gear` with the default shift
 pub const WINDOW_SIZE: usize = mem::size_of::<Digest>() * 8;
 
-pub struct Gear {
-    digest: Wrapping<Digest>,
+/// Mask for chunks of `avg_bytes` bytes on average, rounded up to a power
+/// of two
+///
+/// The low bits of a Gear digest only depend on the last few bytes, so the
+/// mask selects the top bits instead. Edges are where they are all zero:
+/// `find_chunk_edge_mask(buf, mask, 0)`.
+///
+/// Panics if the rounded size needs 64 bits or more.
+pub fn mask_for_average_size(avg_bytes: usize) -> Digest {
+    let bits = avg_bytes.next_power_of_two().trailing_zeros();
+    assert!(bits < 64);
+    !(Digest::MAX >> bits)
+}
+
+/// Gear rolling hash shifting `S` bits per byte
+///
+/// Bytes leave the digest after `64 / S` (rounded up) more bytes are
+/// rolled, so larger shifts trade window size for faster mixing. `S` has to
+/// be between 1 and 63, which is checked at compile time. FastCDC shifts
+/// by 1, see `Gear`; other shifts are built with `Default`, e.g.
+/// `GearShift::<2>::default()`.
+///
+/// ```compile_fail
+/// let gear = rollsum::GearShift::<0>::default();
+/// ```
+pub struct GearShift<const S: u32> {
+    digest: Digest,
+    table: Table,
     chunk_bits: u32,
 }
 
-impl Default for Gear {
+/// Table of a `Gear` engine, shared unless derived from a key
+enum Table {
+    Static(&'static [Digest; 256]),
+    Keyed(Box<[Digest; 256]>),
+}
+
+impl Table {
+    #[inline(always)]
+    fn get(&self) -> &[Digest; 256] {
+        match self {
+            Table::Static(table) => table,
+            Table::Keyed(table) => table,
+        }
+    }
+}
+
+/// Gear rolling hash, as used by FastCDC
+///
+/// ```
+/// use rollsum::{Engine, Gear};
+///
+/// let mut gear1 = Gear::default();
+/// let mut gear2 = Gear::new();
+/// gear1.roll(b"hello, world");
+/// gear2.roll(b"hello, world");
+/// assert_eq!(gear1.digest(), gear2.digest());
+/// ```
+pub type Gear = GearShift<1>;
+
+impl<const S: u32> Default for GearShift<S> {
     fn default() -> Self {
-        Gear {
-            digest: Wrapping(0),
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_SHIFT;
+        GearShift {
+            digest: 0,
+            table: Table::Static(&G),
             chunk_bits: CHUNK_BITS,
         }
     }
@@ -30,30 +87,56 @@ impl Default for Gear {
 
 include!("_gear_rand.rs");
 
-impl Engine for Gear {
+/// Gear state transition: the digest after rolling `byte` into `state`.
+///
+/// This is the whole of `Gear::roll_byte` with the default table, exposed as
+/// a pure function so that alternative implementations can be checked
+/// against it bit for bit.
+#[inline(always)]
+pub fn step(state: Digest, byte: u8) -> Digest {
+    step_with(&G, 1, state, byte)
+}
+
+/// `step` with any table and shift; every `Gear` update goes through this
+#[inline(always)]
+fn step_with(table: &[Digest; 256], shift: u32, state: Digest, byte: u8) -> Digest {
+    (state << shift).wrapping_add(table[byte as usize])
+}
+
+impl<const S: u32> Engine for GearShift<S> {
     type Digest = Digest;
 
     #[inline(always)]
     fn roll_byte(&mut self, b: u8) {
-        self.digest <<= 1;
-        self.digest += Wrapping(G[b as usize]);
+        self.digest = step_with(self.table.get(), S, self.digest, b);
+    }
+
+    #[inline(always)]
+    fn roll_byte_digest(&mut self, b: u8) -> Digest {
+        self.roll_byte(b);
+        self.digest
     }
 
     fn roll(&mut self, buf: &[u8]) {
-        crate::roll_windowed(self, WINDOW_SIZE, buf);
+        crate::roll_windowed(self, buf);
+    }
+
+    fn window_size(&self) -> usize {
+        self.effective_window()
     }
 
     #[inline(always)]
     fn digest(&self) -> Digest {
-        self.digest.0
+        self.digest
     }
 
     #[inline]
     fn reset(&mut self) {
-        *self = Gear {
-            chunk_bits: self.chunk_bits,
-            ..Default::default()
-        }
+        self.digest = 0;
+    }
+
+    fn find_chunk_edge_mask(&mut self, buf: &[u8], mask: Digest, target: Digest) -> Option<usize> {
+        self.scan_mask(buf, mask, target).map(|(offset, _)| offset)
     }
 }
 
@@ -67,6 +150,8 @@ impl Gear {
     ///
     /// `chunk_bits` is number of bits that need to match in
     /// the edge condition. `CHUNK_BITS` constant is the default.
+    ///
+    /// Panics if `chunk_bits` is 32 or more.
     pub fn new_with_chunk_bits(chunk_bits: u32) -> Self {
         assert!(chunk_bits < 32);
         Gear {
@@ -75,40 +160,309 @@ impl Gear {
         }
     }
 
+    /// Create new Gear engine with a table derived from `key`
+    ///
+    /// The table is the ChaCha20 keystream for `key`, so without the key,
+    /// edge positions cannot be predicted from the data alone, nor can
+    /// files be recognized by their sequence of chunk lengths.
+    pub fn with_key(key: &[u8; 32]) -> Self {
+        let mut table = [0; 256];
+        for (counter, entries) in table.chunks_mut(8).enumerate() {
+            let block = chacha20_block(key, counter as u32, &[0; 12]);
+            for (entry, bytes) in entries.iter_mut().zip(block.chunks(8)) {
+                let mut le = [0; 8];
+                le.copy_from_slice(bytes);
+                *entry = Digest::from_le_bytes(le);
+            }
+        }
+        Gear {
+            table: Table::Keyed(Box::new(table)),
+            ..Default::default()
+        }
+    }
+
+    /// Create new Gear engine using `table`
+    ///
+    /// Other Gear implementations each publish their own table; using it
+    /// reproduces their digests. The engine keeps the reference.
+    pub fn with_table(table: &'static [Digest; 256]) -> Self {
+        Gear {
+            table: Table::Static(table),
+            ..Default::default()
+        }
+    }
+}
+
+impl<const S: u32> GearShift<S> {
+    /// Fails to compile for shifts that would leave the digest constant
+    const VALID_SHIFT: () = assert!(S > 0 && S < 64, "Gear shift must be 1..=63");
+
+    /// The number of most recent bytes the digest depends on
+    // `usize::div_ceil` needs Rust 1.73
+    #[allow(clippy::manual_div_ceil)]
+    pub fn effective_window(&self) -> usize {
+        (WINDOW_SIZE + S as usize - 1) / S as usize
+    }
+
     /// Find chunk edge using Gear defaults.
     ///
     /// See `Engine::find_chunk_edge_cond`.
     pub fn find_chunk_edge(&mut self, buf: &[u8]) -> Option<(usize, Digest)> {
-        const DIGEST_SIZE: usize = mem::size_of::<Digest>();
-        let shift = DIGEST_SIZE as u32 - self.chunk_bits;
-        self.find_chunk_edge_cond(buf, |e: &Gear| (e.digest() >> shift) == 0)
+        // The top `chunk_bits` bits have to be zero
+        let mask = !(Digest::MAX >> self.chunk_bits);
+        self.scan_mask(buf, mask, 0)
+    }
+
+    /// `find_chunk_edge_mask`, also returning the digest at the edge
+    fn scan_mask(&mut self, buf: &[u8], mask: Digest, target: Digest) -> Option<(usize, Digest)> {
+        // Keep the digest in a register rather than in `self`
+        let table = self.table.get();
+        let mut digest = self.digest;
+        for (i, &b) in buf.iter().enumerate() {
+            digest = step_with(table, S, digest, b);
+            if digest & mask == target {
+                self.reset();
+                return Some((i + 1, digest));
+            }
+        }
+        self.digest = digest;
+        None
+    }
+}
+
+/// The ChaCha20 block function (RFC 8439, section 2.3)
+fn chacha20_block(key: &[u8; 32], counter: u32, nonce: &[u8; 12]) -> [u8; 64] {
+    fn quarter_round(s: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+        s[a] = s[a].wrapping_add(s[b]);
+        s[d] = (s[d] ^ s[a]).rotate_left(16);
+        s[c] = s[c].wrapping_add(s[d]);
+        s[b] = (s[b] ^ s[c]).rotate_left(12);
+        s[a] = s[a].wrapping_add(s[b]);
+        s[d] = (s[d] ^ s[a]).rotate_left(8);
+        s[c] = s[c].wrapping_add(s[d]);
+        s[b] = (s[b] ^ s[c]).rotate_left(7);
+    }
+
+    let word = |bytes: &[u8]| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    let mut initial = [0u32; 16];
+    initial[..4].copy_from_slice(&[0x61707865, 0x3320646e, 0x79622d32, 0x6b206574]);
+    for (w, bytes) in initial[4..12].iter_mut().zip(key.chunks(4)) {
+        *w = word(bytes);
+    }
+    initial[12] = counter;
+    for (w, bytes) in initial[13..].iter_mut().zip(nonce.chunks(4)) {
+        *w = word(bytes);
+    }
+
+    let mut state = initial;
+    for _ in 0..10 {
+        quarter_round(&mut state, 0, 4, 8, 12);
+        quarter_round(&mut state, 1, 5, 9, 13);
+        quarter_round(&mut state, 2, 6, 10, 14);
+        quarter_round(&mut state, 3, 7, 11, 15);
+        quarter_round(&mut state, 0, 5, 10, 15);
+        quarter_round(&mut state, 1, 6, 11, 12);
+        quarter_round(&mut state, 2, 7, 8, 13);
+        quarter_round(&mut state, 3, 4, 9, 14);
     }
+
+    let mut block = [0; 64];
+    for (bytes, (w, i)) in block.chunks_mut(4).zip(state.iter().zip(initial.iter())) {
+        bytes.copy_from_slice(&w.wrapping_add(*i).to_le_bytes());
+    }
+    block
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn mask_for_average_size() {
+        use nanorand::{Rng, WyRand};
+
+        assert_eq!(super::mask_for_average_size(1), 0);
+        assert_eq!(super::mask_for_average_size(8192), 0xfff8 << 48);
+        assert_eq!(super::mask_for_average_size(5000), 0xfff8 << 48);
+
+        let mut data = vec![0u8; 1 << 20];
+        WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+
+        for &avg in &[1024, 4096] {
+            let mask = super::mask_for_average_size(avg);
+            let mut gear = Gear::new();
+            let mut remaining = &data[..];
+            let mut count = 0;
+            while let Some(offset) = gear.find_chunk_edge_mask(remaining, mask, 0) {
+                remaining = &remaining[offset..];
+                count += 1;
+            }
+            let observed = (data.len() - remaining.len()) / count;
+            assert!(observed * 5 > avg * 4 && observed * 5 < avg * 6);
+        }
+    }
+
     #[test]
     fn effective_window_size() {
-        let ones = vec![0x1; 1024];
-        let zeroes = vec![0x0; 1024];
+        fn check<const S: u32>() {
+            let ones = vec![0x1; 1024];
+            let zeroes = vec![0x0; 1024];
 
-        let mut gear = Gear::new();
-        gear.roll(&ones);
-        let digest = gear.digest();
+            let mut gear = GearShift::<S>::default();
+            gear.roll(&ones);
+            let digest = gear.digest();
 
-        let mut gear = Gear::new();
-        gear.roll(&zeroes);
+            let mut gear = GearShift::<S>::default();
+            gear.roll(&zeroes);
+
+            let window = ones.iter().position(|&b| {
+                let found = gear.digest() == digest;
+                gear.roll_byte(b);
+                found
+            });
+            assert_eq!(window, Some(gear.effective_window()));
+        }
+
+        check::<1>();
+        check::<2>();
+        check::<3>();
+        check::<8>();
+        check::<63>();
+        assert_eq!(Gear::new().effective_window(), WINDOW_SIZE);
+        assert_eq!(GearShift::<2>::default().effective_window(), 32);
+        assert_eq!(GearShift::<3>::default().effective_window(), 22);
+    }
+
+    #[test]
+    fn find_chunk_edge_uses_top_bits() {
+        use nanorand::{Rng, WyRand};
+
+        let mut data = vec![0u8; 256 * 1024];
+        WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+
+        let mut gear1 = Gear::new();
+        let mut gear2 = Gear::new();
+        let mut remaining = &data[..];
+        while let Some((i, digest)) = gear1.find_chunk_edge(remaining) {
+            assert_eq!(digest >> (64 - CHUNK_BITS), 0);
+            let expected = gear2.find_chunk_edge_cond(remaining, |e| e.digest() >> 51 == 0);
+            assert_eq!(Some((i, digest)), expected);
+            remaining = &remaining[i..];
+        }
+        assert_eq!(
+            gear2.find_chunk_edge_cond(remaining, |e| e.digest() >> 51 == 0),
+            None
+        );
+    }
 
-        for (i, &b) in ones.iter().enumerate() {
-            if gear.digest() == digest {
-                assert_eq!(i, WINDOW_SIZE);
-                return;
+    #[test]
+    fn roll_same_as_roll_byte() {
+        use nanorand::{Rng, WyRand};
+
+        fn check<const S: u32>(data: &[u8]) {
+            for &len in &[0, 1, 2, 3, 63, 64, 65, 66, 4095] {
+                let mut gear1 = GearShift::<S>::default();
+                let mut gear2 = GearShift::<S>::default();
+                gear1.roll(&data[..len]);
+                data[..len].iter().for_each(|&b| gear2.roll_byte(b));
+                assert_eq!(gear1.digest(), gear2.digest());
             }
+        }
+
+        let mut data = vec![0u8; 4096];
+        WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+
+        check::<1>(&data);
+        check::<2>(&data);
+        check::<3>(&data);
+        check::<40>(&data);
+        check::<63>(&data);
+    }
+
+    #[test]
+    fn step_same_as_roll_byte() {
+        use nanorand::{Rng, WyRand};
+
+        let mut data = vec![0u8; 4096];
+        WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+
+        let mut gear = Gear::new();
+        let mut state = 0;
+        for &b in &data {
             gear.roll_byte(b);
+            state = step(state, b);
+            assert_eq!(gear.digest(), state);
         }
+    }
+
+    #[test]
+    fn chacha20_rfc8439_vector() {
+        let mut key = [0; 32];
+        key.iter_mut().enumerate().for_each(|(i, k)| *k = i as u8);
+        let nonce = [0, 0, 0, 0x09, 0, 0, 0, 0x4a, 0, 0, 0, 0];
+        let block = chacha20_block(&key, 1, &nonce);
+        assert_eq!(
+            block[..16],
+            [
+                0x10, 0xf1, 0xe7, 0xe4, 0xd1, 0x3b, 0x59, 0x15, 0x50, 0x0f, 0xdd, 0x1f, 0xa3, 0x20,
+                0x71, 0xc4
+            ]
+        );
+        assert_eq!(
+            block[48..],
+            [
+                0xb5, 0x12, 0x9c, 0xd1, 0xde, 0x16, 0x4e, 0xb9, 0xcb, 0xd0, 0x83, 0xe8, 0xa2, 0x50,
+                0x3c, 0x4e
+            ]
+        );
+    }
+
+    #[test]
+    fn with_table() {
+        static IDENTITY: [Digest; 256] = {
+            let mut table = [0; 256];
+            let mut i = 0;
+            while i < 256 {
+                table[i] = i as Digest;
+                i += 1;
+            }
+            table
+        };
+
+        // ((0x01 << 1) + 0x02 << 1) + 0x03
+        let mut gear = Gear::with_table(&IDENTITY);
+        assert!(std::ptr::eq(gear.table.get(), &IDENTITY));
+        gear.roll(&[1, 2, 3]);
+        assert_eq!(gear.digest(), 0x0b);
+
+        let mut gear1 = Gear::with_table(&G);
+        let mut gear2 = Gear::new();
+        gear1.roll(b"hello world");
+        gear2.roll(b"hello world");
+        assert_eq!(gear1.digest(), gear2.digest());
+    }
+
+    #[test]
+    fn key_changes_digest() {
+        use nanorand::{Rng, WyRand};
+
+        let mut data = vec![0u8; 1024];
+        WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+
+        let mut gear1 = Gear::with_key(&[1; 32]);
+        let mut gear2 = Gear::with_key(&[1; 32]);
+        let mut gear3 = Gear::with_key(&[2; 32]);
+        let mut gear4 = Gear::new();
+        gear1.roll(&data);
+        gear2.roll(&data);
+        gear3.roll(&data);
+        gear4.roll(&data);
+        assert_eq!(gear1.digest(), gear2.digest());
+        assert_ne!(gear1.digest(), gear3.digest());
+        assert_ne!(gear1.digest(), gear4.digest());
 
-        panic!("matching digest not found");
+        gear1.reset();
+        gear1.roll(&data);
+        assert_eq!(gear1.digest(), gear2.digest());
     }
 }
@@ -0,0 +1,128 @@
+use super::Engine;
+use std::default::Default;
+use std::mem;
+
+pub type Digest = u128;
+
+/// Default chunk size used by `gear128`
+pub const CHUNK_SIZE: u32 = 1 << CHUNK_BITS;
+
+/// Default chunk size used by `gear128` (log2)
+pub const CHUNK_BITS: u32 = 13;
+
+/// The effective window size used by `gear128`
+pub const WINDOW_SIZE: usize = mem::size_of::<Digest>() * 8;
+
+include!("_gear_rand.rs");
+
+/// The `gear` table in the high 64 bits, and SplitMix64 output in the low
+/// 64 bits
+const G128: [Digest; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = ((G[i] as Digest) << 64) | crate::splitmix64(0, i as u64) as Digest;
+        i += 1;
+    }
+    table
+};
+
+/// `Gear` with a 128-bit digest
+///
+/// The top bits, which decide chunk edges, depend on the last 128 bytes
+/// rather than 64, so even large chunk sizes draw on a full window.
+pub struct Gear128 {
+    digest: Digest,
+    chunk_bits: u32,
+}
+
+impl Default for Gear128 {
+    fn default() -> Self {
+        Gear128 {
+            digest: 0,
+            chunk_bits: CHUNK_BITS,
+        }
+    }
+}
+
+/// Gear128 state transition: the digest after rolling `byte` into `state`.
+///
+/// See `gear::step`.
+#[inline(always)]
+pub fn step(state: Digest, byte: u8) -> Digest {
+    (state << 1).wrapping_add(G128[byte as usize])
+}
+
+impl Engine for Gear128 {
+    type Digest = Digest;
+
+    #[inline(always)]
+    fn roll_byte(&mut self, b: u8) {
+        self.digest = step(self.digest, b);
+    }
+
+    fn roll(&mut self, buf: &[u8]) {
+        crate::roll_windowed(self, buf);
+    }
+
+    fn window_size(&self) -> usize {
+        WINDOW_SIZE
+    }
+
+    #[inline(always)]
+    fn digest(&self) -> Digest {
+        self.digest
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.digest = 0;
+    }
+}
+
+impl Gear128 {
+    /// Create new Gear128 engine with default chunking settings
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create new Gear128 engine with custom chunking settings
+    ///
+    /// `chunk_bits` is number of bits that need to match in
+    /// the edge condition. `CHUNK_BITS` constant is the default.
+    ///
+    /// Panics if `chunk_bits` is 128 or more.
+    pub fn new_with_chunk_bits(chunk_bits: u32) -> Self {
+        assert!(chunk_bits < 128);
+        Gear128 {
+            chunk_bits,
+            ..Default::default()
+        }
+    }
+
+    /// Find chunk edge using Gear128 defaults.
+    ///
+    /// See `Engine::find_chunk_edge_cond`.
+    pub fn find_chunk_edge(&mut self, buf: &[u8]) -> Option<(usize, Digest)> {
+        // The top `chunk_bits` bits have to be zero
+        let mask = !(Digest::MAX >> self.chunk_bits);
+        self.find_chunk_edge_cond(buf, |e: &Gear128| e.digest() & mask == 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn top_bits_depend_on_whole_window() {
+        // A byte 100 positions back still changes the top 20 bits
+        let mut data = vec![0u8; WINDOW_SIZE];
+        let mut gear1 = Gear128::new();
+        gear1.roll(&data);
+        data[WINDOW_SIZE - 101] = 1;
+        let mut gear2 = Gear128::new();
+        gear2.roll(&data);
+        assert_ne!(gear1.digest() >> 108, gear2.digest() >> 108);
+    }
+}
@@ -0,0 +1,132 @@
+use super::Engine;
+use std::default::Default;
+use std::mem;
+
+pub type Digest = u32;
+
+/// Default chunk size used by `gear32`
+pub const CHUNK_SIZE: u32 = 1 << CHUNK_BITS;
+
+/// Default chunk size used by `gear32` (log2)
+pub const CHUNK_BITS: u32 = 13;
+
+/// The effective window size used by `gear32`
+pub const WINDOW_SIZE: usize = mem::size_of::<Digest>() * 8;
+
+include!("_gear_rand.rs");
+
+/// The `gear` table truncated to its low 32 bits
+const G32: [Digest; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = G[i] as Digest;
+        i += 1;
+    }
+    table
+};
+
+/// `Gear` with a 32-bit digest, for targets without native 64-bit
+/// arithmetic
+pub struct Gear32 {
+    digest: Digest,
+    chunk_bits: u32,
+}
+
+impl Default for Gear32 {
+    fn default() -> Self {
+        Gear32 {
+            digest: 0,
+            chunk_bits: CHUNK_BITS,
+        }
+    }
+}
+
+/// Gear32 state transition: the digest after rolling `byte` into `state`.
+///
+/// See `gear::step`.
+#[inline(always)]
+pub fn step(state: Digest, byte: u8) -> Digest {
+    (state << 1).wrapping_add(G32[byte as usize])
+}
+
+impl Engine for Gear32 {
+    type Digest = Digest;
+
+    #[inline(always)]
+    fn roll_byte(&mut self, b: u8) {
+        self.digest = step(self.digest, b);
+    }
+
+    fn roll(&mut self, buf: &[u8]) {
+        crate::roll_windowed(self, buf);
+    }
+
+    fn window_size(&self) -> usize {
+        WINDOW_SIZE
+    }
+
+    #[inline(always)]
+    fn digest(&self) -> Digest {
+        self.digest
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.digest = 0;
+    }
+}
+
+impl Gear32 {
+    /// Create new Gear32 engine with default chunking settings
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create new Gear32 engine with custom chunking settings
+    ///
+    /// `chunk_bits` is number of bits that need to match in
+    /// the edge condition. `CHUNK_BITS` constant is the default.
+    ///
+    /// Panics if `chunk_bits` is 32 or more.
+    pub fn new_with_chunk_bits(chunk_bits: u32) -> Self {
+        assert!(chunk_bits < 32);
+        Gear32 {
+            chunk_bits,
+            ..Default::default()
+        }
+    }
+
+    /// Find chunk edge using Gear32 defaults.
+    ///
+    /// See `Engine::find_chunk_edge_cond`.
+    pub fn find_chunk_edge(&mut self, buf: &[u8]) -> Option<(usize, Digest)> {
+        // The top `chunk_bits` bits have to be zero
+        let mask = !(Digest::MAX >> self.chunk_bits);
+        self.find_chunk_edge_cond(buf, |e: &Gear32| e.digest() & mask == 0)
+    }
+}
+
+// These tests compare Gear32 with `Gear`
+#[cfg(all(test, feature = "gear"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn low_half_of_gear() {
+        use nanorand::{Rng, WyRand};
+
+        let mut data = vec![0u8; 4096];
+        WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+
+        // Carries only move upwards, so truncating the table truncates
+        // the digest
+        let mut gear = crate::Gear::new();
+        let mut gear32 = Gear32::new();
+        for &b in &data {
+            gear.roll_byte(b);
+            gear32.roll_byte(b);
+            assert_eq!(gear.digest() as Digest, gear32.digest());
+        }
+    }
+}
@@ -3,12 +3,76 @@
 #[cfg(feature = "bup")]
 pub mod bup;
 #[cfg(feature = "bup")]
-pub use crate::bup::Bup;
+pub use crate::bup::{Bup, BupWide, RollSum, RollSumWide, SeededBup};
 
 #[cfg(feature = "gear")]
 pub mod gear;
 #[cfg(feature = "gear")]
-pub use crate::gear::Gear;
+pub use crate::gear::{Gear, GearShift};
+
+#[cfg(feature = "gear32")]
+pub mod gear32;
+#[cfg(feature = "gear32")]
+pub use crate::gear32::Gear32;
+
+#[cfg(feature = "gear128")]
+pub mod gear128;
+#[cfg(feature = "gear128")]
+pub use crate::gear128::Gear128;
+
+/// Cyclic polynomial rolling hash of the kind used by
+/// `borg` - https://github.com/borgbackup/borg/ (given borg's table)
+#[cfg(feature = "buzhash")]
+pub mod buzhash;
+#[cfg(feature = "buzhash")]
+pub use crate::buzhash::Buzhash;
+
+/// Rabin fingerprinting used by
+/// `restic` - https://github.com/restic/restic/
+#[cfg(feature = "rabin")]
+pub mod rabin;
+#[cfg(feature = "rabin")]
+pub use crate::rabin::Rabin;
+
+/// Weak rolling checksum used by
+/// `rsync` - https://rsync.samba.org/
+#[cfg(feature = "rsync")]
+pub mod rsync;
+#[cfg(feature = "rsync")]
+pub use crate::rsync::RsyncSum;
+
+/// RabinKarp rolling hash, after the one in
+/// `librsync` - https://librsync.github.io/
+#[cfg(feature = "rabinkarp")]
+pub mod rabinkarp;
+#[cfg(feature = "rabinkarp")]
+pub use crate::rabinkarp::RabinKarp;
+
+/// Rolling hash by cyclic polynomial
+#[cfg(feature = "cyclicpoly")]
+pub mod cyclicpoly;
+#[cfg(feature = "cyclicpoly")]
+pub use crate::cyclicpoly::{CyclicHash, CyclicPoly};
+
+/// Multiply-and-add rolling hash
+#[cfg(feature = "polyhash")]
+pub mod polyhash;
+#[cfg(feature = "polyhash")]
+pub use crate::polyhash::PolyHash;
+
+/// CRC-32 over a sliding window
+#[cfg(feature = "crc32")]
+pub mod crc32;
+#[cfg(feature = "crc32")]
+pub use crate::crc32::RollingCrc32;
+
+/// Engines built from functions adding and removing single bytes
+pub mod windowed;
+pub use crate::windowed::{WindowFn, Windowed};
+
+/// Two engines rolled in a single pass
+pub mod tandem;
+pub use crate::tandem::Tandem;
 
 /// Rolling sum engine trait
 pub trait Engine {
@@ -22,9 +86,26 @@ pub trait Engine {
         buf.iter().for_each(|&b| self.roll_byte(b));
     }
 
+    /// Number of most recent bytes the digest depends on
+    ///
+    /// Rolling this many bytes fully replaces whatever was rolled before,
+    /// so `roll` only needs to look at the last `window_size()` bytes.
+    ///
+    /// Defaults to `usize::MAX`, meaning unknown: every byte is rolled.
+    fn window_size(&self) -> usize {
+        usize::MAX
+    }
+
     /// Return current rolling sum digest
     fn digest(&self) -> Self::Digest;
 
+    /// Roll over one byte and return the new digest
+    #[inline]
+    fn roll_byte_digest(&mut self, byte: u8) -> Self::Digest {
+        self.roll_byte(byte);
+        self.digest()
+    }
+
     /// Resets the internal state
     fn reset(&mut self);
 
@@ -47,26 +128,59 @@ pub trait Engine {
         F: Fn(&Self) -> bool,
     {
         for (i, &b) in buf.iter().enumerate() {
-            self.roll_byte(b);
+            let digest = self.roll_byte_digest(b);
 
             if cond(self) {
-                let digest = self.digest();
                 self.reset();
                 return Some((i + 1, digest));
             }
         }
         None
     }
+
+    /// Find the end of the chunk, where `digest & mask == target`.
+    ///
+    /// Same as `find_chunk_edge_cond` with that condition, but without the
+    /// closure, so engines can provide a faster loop. Only the offset is
+    /// returned, the digest is always `target` in the masked bits.
+    fn find_chunk_edge_mask(
+        &mut self,
+        buf: &[u8],
+        mask: Self::Digest,
+        target: Self::Digest,
+    ) -> Option<usize>
+    where
+        Self::Digest: Copy + PartialEq + std::ops::BitAnd<Output = Self::Digest>,
+    {
+        self.find_chunk_edge_cond(buf, |e: &Self| e.digest() & mask == target)
+            .map(|(offset, _)| offset)
+    }
 }
 
 #[inline]
-fn roll_windowed<E: Engine>(engine: &mut E, window_size: usize, data: &[u8]) {
-    let last_window = data.windows(window_size).next_back().unwrap_or(data);
+fn roll_windowed<E: Engine>(engine: &mut E, data: &[u8]) {
+    let start = data.len().saturating_sub(engine.window_size());
+    let last_window = &data[start..];
     for &b in last_window {
         engine.roll_byte(b);
     }
 }
 
+/// Output number `n` (from 0) of SplitMix64 seeded with `seed`, used to
+/// expand a seed into tables and polynomials
+#[cfg(any(
+    feature = "bup",
+    feature = "cyclicpoly",
+    feature = "gear128",
+    feature = "rabin"
+))]
+const fn splitmix64(seed: u64, n: u64) -> u64 {
+    let mut z = seed.wrapping_add((n + 1).wrapping_mul(0x9e3779b97f4a7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -79,8 +193,67 @@ mod tests {
         data
     }
 
+    /// Engine relying on the default `window_size`
+    #[derive(Default)]
+    struct Sum(u64);
+
+    impl Engine for Sum {
+        type Digest = u64;
+
+        fn roll_byte(&mut self, byte: u8) {
+            self.0 += byte as u64;
+        }
+
+        fn digest(&self) -> u64 {
+            self.0
+        }
+
+        fn reset(&mut self) {
+            self.0 = 0;
+        }
+    }
+
+    #[test]
+    fn default_window_size_rolls_everything() {
+        let data = rand_data(1024);
+        let mut sum = Sum::default();
+        roll_windowed(&mut sum, &data);
+        assert_eq!(sum.digest(), data.iter().map(|&b| b as u64).sum());
+    }
+
+    /// Roll `4 * W + 100` bytes of `rand_data` into `engine`, where `W` is
+    /// its window size, and compare every digest after the window fills up
+    /// with `reference` of the window
+    #[cfg(any(
+        feature = "buzhash",
+        feature = "cyclicpoly",
+        feature = "crc32",
+        feature = "polyhash",
+        feature = "rabin"
+    ))]
+    pub(crate) fn check_against_reference<E, F>(mut engine: E, reference: F)
+    where
+        E: Engine,
+        E::Digest: PartialEq + std::fmt::Debug,
+        F: Fn(&[u8]) -> E::Digest,
+    {
+        let w = engine.window_size();
+        let data = rand_data(4 * w + 100);
+        for (i, &b) in data.iter().enumerate() {
+            engine.roll_byte(b);
+            if i + 1 >= w {
+                assert_eq!(engine.digest(), reference(&data[i + 1 - w..=i]));
+            }
+        }
+    }
+
+    // Unused when no engine features are enabled
+    #[allow(unused_macros)]
     macro_rules! test_engine {
         ($name:ident, $engine:ty) => {
+            test_engine!($name, $engine, 31);
+        };
+        ($name:ident, $engine:ty, $max_chunk_bits:expr) => {
             mod $name {
                 use super::*;
 
@@ -103,6 +276,80 @@ mod tests {
                     }
                 }
 
+                #[test]
+                fn roll_byte_digest_same_as_digest() {
+                    let mut engine1 = <$engine>::default();
+                    let mut engine2 = <$engine>::default();
+                    for &b in &rand_data(1024) {
+                        engine1.roll_byte(b);
+                        assert_eq!(engine2.roll_byte_digest(b), engine1.digest());
+                        assert_eq!(engine2.digest(), engine1.digest());
+                    }
+                }
+
+                #[test]
+                fn find_chunk_edge_mask_same_as_cond() {
+                    type Digest = <$engine as Engine>::Digest;
+                    let data = rand_data(64 * 1024);
+                    for &(mask, target) in &[(0x3ffu32, 0u32), (0x3ff, 0x3ff), (0x3ff, 0x155)] {
+                        let (mask, target) = (Digest::from(mask), Digest::from(target));
+                        let mut engine1 = <$engine>::default();
+                        let mut engine2 = <$engine>::default();
+                        // Odd-sized pieces check that the state carries over between calls
+                        for piece in data.chunks(1001) {
+                            let mut remaining = piece;
+                            loop {
+                                let edge1 = engine1
+                                    .find_chunk_edge_cond(remaining, |e: &$engine| {
+                                        e.digest() & mask == target
+                                    })
+                                    .map(|(offset, _)| offset);
+                                let edge2 = engine2.find_chunk_edge_mask(remaining, mask, target);
+                                assert_eq!(edge1, edge2);
+                                assert_eq!(engine1.digest(), engine2.digest());
+                                match edge1 {
+                                    Some(offset) => remaining = &remaining[offset..],
+                                    None => break,
+                                }
+                            }
+                        }
+                    }
+                }
+
+                #[test]
+                fn digest_depends_on_window_only() {
+                    let data = rand_data(4096);
+                    let mut engine1 = <$engine>::default();
+                    data.iter().for_each(|&b| engine1.roll_byte(b));
+
+                    let window = &data[data.len() - engine1.window_size()..];
+                    let mut engine2 = <$engine>::default();
+                    window.iter().for_each(|&b| engine2.roll_byte(b));
+                    assert_eq!(engine1.digest(), engine2.digest());
+                }
+
+                #[test]
+                fn window_size_is_exact() {
+                    let mut engine = <$engine>::default();
+                    let w = engine.window_size();
+                    let tail = rand_data(w);
+
+                    // Digests after a window of `prefix` bytes and then `tail`,
+                    // for every `prefix`, from one engine reset in between
+                    let mut digests_after = |tail: &[u8]| {
+                        let mut digests = std::collections::HashSet::new();
+                        for prefix in 0..=255 {
+                            engine.reset();
+                            (0..w).for_each(|_| engine.roll_byte(prefix));
+                            tail.iter().for_each(|&b| engine.roll_byte(b));
+                            digests.insert(engine.digest());
+                        }
+                        digests.len()
+                    };
+                    assert_eq!(digests_after(&tail), 1);
+                    assert!(digests_after(&tail[1..]) > 1);
+                }
+
                 #[test]
                 fn chunk_edge_correct_digest() {
                     let mut engine1 = <$engine>::default();
@@ -121,6 +368,35 @@ mod tests {
                         assert_eq!(engine2.digest(), engine1.digest());
                     }
                 }
+
+                #[test]
+                fn no_panic_on_extreme_inputs() {
+                    let data = rand_data(4096);
+                    let zeroes = vec![0; 256 * 1024];
+
+                    for &chunk_bits in &[0, 1, 13, $max_chunk_bits] {
+                        let mut engine = <$engine>::new_with_chunk_bits(chunk_bits);
+                        engine.roll(&[]);
+                        assert_eq!(engine.find_chunk_edge(&[]), None);
+                        assert_eq!(engine.find_chunk_edge_cond(&[], |_| true), None);
+
+                        for &len in &[1, 63, 64, 65, data.len()] {
+                            engine.roll(&data[..len]);
+                            let mut remaining = &data[..len];
+                            while let Some((i, _)) = engine.find_chunk_edge(remaining) {
+                                assert!(i > 0 && i <= remaining.len());
+                                remaining = &remaining[i..];
+                            }
+                        }
+
+                        let mut remaining = &zeroes[..];
+                        while let Some((i, _)) = engine.find_chunk_edge(remaining) {
+                            assert!(i > 0 && i <= remaining.len());
+                            remaining = &remaining[i..];
+                        }
+                        engine.reset();
+                    }
+                }
             }
         };
     }
@@ -128,6 +404,493 @@ mod tests {
     #[cfg(feature = "bup")]
     test_engine!(bup, Bup);
 
+    #[cfg(feature = "bup")]
+    test_engine!(bup_wide, BupWide, BupWide::MAX_CHUNK_BITS);
+
     #[cfg(feature = "gear")]
     test_engine!(gear, Gear);
+
+    #[cfg(feature = "gear32")]
+    test_engine!(gear32, Gear32);
+
+    #[cfg(feature = "gear128")]
+    test_engine!(gear128, Gear128);
+
+    #[cfg(feature = "buzhash")]
+    test_engine!(buzhash, Buzhash);
+
+    #[cfg(feature = "rabin")]
+    test_engine!(rabin, Rabin);
+
+    #[cfg(feature = "rsync")]
+    test_engine!(rsync, RsyncSum);
+
+    #[cfg(feature = "rabinkarp")]
+    test_engine!(rabinkarp, RabinKarp);
+
+    #[cfg(feature = "cyclicpoly")]
+    test_engine!(cyclicpoly, CyclicPoly);
+
+    #[cfg(feature = "polyhash")]
+    test_engine!(polyhash, PolyHash);
+
+    #[cfg(feature = "crc32")]
+    test_engine!(crc32, RollingCrc32);
+
+    /// Empty, 1-byte, long repetitive and random inputs, and boundary
+    /// parameters for every public function, checked against rolling byte
+    /// by byte or against known values. New public functions need a case
+    /// here; constructors and `find_chunk_edge` with default parameters are
+    /// covered by `no_panic_on_extreme_inputs` above.
+    mod adversarial {
+        use super::*;
+        use std::fmt::Debug;
+
+        fn inputs() -> Vec<Vec<u8>> {
+            vec![
+                vec![],
+                vec![0],
+                vec![0xff],
+                vec![0; 1 << 18],
+                vec![0xff; 1 << 18],
+                [0x00, 0xff].repeat(1 << 17),
+                rand_data(64 * 1024),
+            ]
+        }
+
+        /// Feed every input to an engine from `make` whole, then a byte at a
+        /// time, and check that the edges found are within bounds, and that
+        /// the digests at edges and after each input are those of a fresh
+        /// engine rolling the chunk byte by byte
+        fn assert_edges_consistent<E: Engine>(
+            make: impl Fn() -> E,
+            mut find: impl FnMut(&mut E, &[u8]) -> Option<(usize, E::Digest)>,
+        ) where
+            E::Digest: PartialEq + Debug,
+        {
+            let mut engine = make();
+            let mut reference = make();
+            let mut assert_chunk_digest = |chunk: &mut Vec<u8>, digest: E::Digest| {
+                reference.reset();
+                chunk.iter().for_each(|&b| reference.roll_byte(b));
+                assert_eq!(reference.digest(), digest);
+                chunk.clear();
+            };
+
+            for data in inputs() {
+                let mut chunk = vec![];
+                let mut remaining = &data[..];
+                while let Some((i, digest)) = find(&mut engine, remaining) {
+                    assert!(i > 0 && i <= remaining.len());
+                    chunk.extend_from_slice(&remaining[..i]);
+                    assert_chunk_digest(&mut chunk, digest);
+                    remaining = &remaining[i..];
+                }
+                chunk.extend_from_slice(remaining);
+
+                for byte in data.chunks(1).take(4096) {
+                    chunk.extend_from_slice(byte);
+                    if let Some((i, digest)) = find(&mut engine, byte) {
+                        assert_eq!(i, 1);
+                        assert_chunk_digest(&mut chunk, digest);
+                    }
+                }
+                assert_chunk_digest(&mut chunk, engine.digest());
+                engine.reset();
+            }
+        }
+
+        #[test]
+        fn tandem() {
+            let conds = [
+                |_: &Sum| true,
+                |_: &Sum| false,
+                |e: &Sum| e.digest() & 3 == 0,
+            ];
+            let make = || Tandem::new(Sum::default(), Sum::default());
+            for &a in &conds {
+                for &b in &conds {
+                    assert_edges_consistent(make, |t, buf| t.find_chunk_edge_either(buf, a, b));
+                    assert_edges_consistent(make, |t, buf| t.find_chunk_edge_both(buf, a, b));
+                }
+            }
+        }
+
+        /// Xor of the bytes in the window
+        struct Xor;
+
+        impl WindowFn for Xor {
+            type State = u8;
+            type Digest = u8;
+
+            fn initial(&self, _window_size: usize) -> u8 {
+                0
+            }
+
+            fn fold(&self, state: u8, byte: u8) -> u8 {
+                state ^ byte
+            }
+
+            fn unfold(&self, state: u8, byte: u8, _window_size: usize) -> u8 {
+                state ^ byte
+            }
+
+            fn digest(&self, state: &u8) -> u8 {
+                *state
+            }
+        }
+
+        #[test]
+        fn windowed() {
+            assert_edges_consistent(
+                || Windowed::<_, 1>::new(Xor),
+                |e, buf| e.find_chunk_edge_cond(buf, |e| e.digest() == 0),
+            );
+            assert_edges_consistent(
+                || Windowed::<_, 4096>::new(Xor),
+                |e, buf| e.find_chunk_edge_cond(buf, |e| e.digest() == 0xff),
+            );
+        }
+
+        #[cfg(feature = "bup")]
+        #[test]
+        fn bup() {
+            use crate::bup::{self, BupState};
+
+            let extremes = [0, 1, usize::MAX / 2, usize::MAX];
+            for &s1 in &extremes {
+                for &s2 in &extremes {
+                    let state = BupState { s1, s2 };
+                    let wide = state.digest_wide();
+                    assert_eq!(
+                        state.digest(),
+                        ((wide >> 16) as u32 & 0xffff_0000) | (wide as u32 & 0xffff)
+                    );
+                    for &(drop, add) in &[(0, 0), (0, 0xff), (0xff, 0), (0xff, 0xff)] {
+                        let next = bup::step(state, drop, add);
+                        assert_eq!(next, Bup::step(state, drop, add));
+                        let delta = (add as usize).wrapping_sub(drop as usize);
+                        assert_eq!(next.s1, s1.wrapping_add(delta));
+                        assert_eq!(RollSum::<1, 0>::step(state, drop, add).s1, next.s1);
+                        assert_eq!(RollSum::<4096, 255>::step(state, drop, add).s1, next.s1);
+                    }
+                }
+            }
+
+            // Rolling a window of zeroes over one keeps the state unchanged
+            fn assert_initial_state_stable<const W: usize, const O: usize>() {
+                let state = RollSum::<W, O>::initial_state();
+                let rolled = (0..W).fold(state, |s, _| RollSum::<W, O>::step(s, 0, 0));
+                assert_eq!(rolled, state);
+            }
+            assert_initial_state_stable::<1, 0>();
+            assert_initial_state_stable::<1, { usize::MAX }>();
+            assert_initial_state_stable::<4096, 255>();
+
+            for &seed in &[0, u64::MAX] {
+                assert_edges_consistent(|| Bup::with_seed(seed), |e, buf| e.find_chunk_edge(buf));
+            }
+            assert_edges_consistent(
+                || BupWide::new_with_chunk_bits(0),
+                |e, buf| e.find_chunk_edge(buf),
+            );
+
+            for &chunk_bits in &[0, 31] {
+                let mut bup = Bup::new_with_chunk_bits(chunk_bits);
+                bup.roll(&[0xff; 64]);
+                assert_eq!(bup.digest_wide() as u16, bup.digest() as u16);
+                assert_eq!(bup.count_bits(0), chunk_bits);
+                assert_eq!(bup.count_bits(1), chunk_bits);
+                assert_eq!(bup.count_bits(u32::MAX), 31);
+            }
+
+            for &avg_bytes in &[0, 1, 3, 1 << 30] {
+                let mask = bup::mask_for_average_size(avg_bytes);
+                assert_eq!(mask as usize + 1, avg_bytes.next_power_of_two());
+            }
+        }
+
+        #[cfg(feature = "bup")]
+        #[test]
+        fn tree_splitter() {
+            use crate::bup::TreeSplitter;
+
+            for &(chunk_bits, fanout_bits) in &[(0, 1), (13, 4), (31, 1), (31, u32::MAX)] {
+                let mut splitter = TreeSplitter::new_with_bits(chunk_bits, fanout_bits);
+                for data in inputs() {
+                    let mut total = 0;
+                    splitter.split(&data, |len, _| total += len);
+                    for byte in data.chunks(1).take(4096) {
+                        splitter.split(byte, |len, _| total += len);
+                    }
+                    splitter.split(&[], |_, _| unreachable!());
+                    assert_eq!(
+                        total + splitter.pending(),
+                        data.len() + data.len().min(4096)
+                    );
+                    splitter = TreeSplitter::new_with_bits(chunk_bits, fanout_bits);
+                }
+            }
+            assert_eq!(TreeSplitter::new().pending(), 0);
+        }
+
+        #[cfg(feature = "gear")]
+        #[test]
+        fn gear() {
+            use crate::gear;
+
+            static SATURATED: [u64; 256] = [u64::MAX; 256];
+            for &state in &[0, u64::MAX] {
+                assert_eq!(
+                    gear::step(state, 0xff),
+                    (state << 1).wrapping_add(gear::step(0, 0xff))
+                );
+            }
+            for &key in &[[0; 32], [0xff; 32]] {
+                assert_edges_consistent(|| Gear::with_key(&key), |e, buf| e.find_chunk_edge(buf));
+            }
+            assert_edges_consistent(
+                || Gear::with_table(&SATURATED),
+                |e, buf| e.find_chunk_edge(buf),
+            );
+
+            assert_eq!(GearShift::<63>::default().effective_window(), 2);
+            for &avg_bytes in &[0, 1, 3, 1 << 63] {
+                let mask = gear::mask_for_average_size(avg_bytes);
+                assert_eq!(
+                    mask.count_ones(),
+                    avg_bytes.next_power_of_two().trailing_zeros()
+                );
+                assert_eq!(mask.leading_ones(), mask.count_ones());
+            }
+        }
+
+        #[cfg(feature = "gear32")]
+        #[test]
+        fn gear32() {
+            use crate::gear32;
+
+            for &state in &[0, u32::MAX] {
+                assert_eq!(
+                    gear32::step(state, 0xff),
+                    (state << 1).wrapping_add(gear32::step(0, 0xff))
+                );
+            }
+        }
+
+        #[cfg(feature = "gear128")]
+        #[test]
+        fn gear128() {
+            use crate::gear128;
+
+            for &state in &[0, u128::MAX] {
+                assert_eq!(
+                    gear128::step(state, 0xff),
+                    (state << 1).wrapping_add(gear128::step(0, 0xff))
+                );
+            }
+        }
+
+        #[cfg(feature = "buzhash")]
+        #[test]
+        fn buzhash() {
+            for &seed in &[0, u32::MAX] {
+                assert_edges_consistent(
+                    || Buzhash::with_seed(seed),
+                    |e, buf| e.find_chunk_edge(buf),
+                );
+            }
+            assert_edges_consistent(
+                || CyclicHash::<u32, 1>::with_table(&[u32::MAX; 256], u32::MAX),
+                |e, buf| e.find_chunk_edge(buf),
+            );
+        }
+
+        #[cfg(feature = "crc32")]
+        #[test]
+        fn crc32() {
+            for &pol in &[0, 1, u32::MAX, crate::crc32::CASTAGNOLI] {
+                assert_edges_consistent(
+                    || RollingCrc32::<1>::with_polynomial(pol),
+                    |e, buf| e.find_chunk_edge(buf),
+                );
+            }
+        }
+
+        #[cfg(feature = "cyclicpoly")]
+        #[test]
+        fn cyclicpoly() {
+            for &seed in &[0, u64::MAX] {
+                assert_edges_consistent(
+                    || CyclicPoly::with_seed(seed),
+                    |e, buf| e.find_chunk_edge(buf),
+                );
+            }
+            assert_edges_consistent(
+                || CyclicHash::<u64, 1>::with_table([u64::MAX; 256]),
+                |e, buf| e.find_chunk_edge(buf),
+            );
+        }
+
+        #[cfg(feature = "polyhash")]
+        #[test]
+        fn polyhash() {
+            for &multiplier in &[0, 1, u64::MAX] {
+                assert_edges_consistent(
+                    || PolyHash::<1>::with_multiplier(multiplier),
+                    |e, buf| e.find_chunk_edge(buf),
+                );
+            }
+        }
+
+        #[cfg(feature = "rabin")]
+        #[test]
+        fn rabin() {
+            use crate::rabin;
+
+            let pols = [0, 1, 2, 3, 1 << 63, u64::MAX];
+            let irreducible: Vec<_> = pols.iter().map(|&pol| rabin::is_irreducible(pol)).collect();
+            assert_eq!(irreducible, [false, false, true, true, false, false]);
+            for &seed in &[0, u64::MAX] {
+                assert!(rabin::is_irreducible(rabin::random_polynomial(seed)));
+            }
+            // Reducible polynomials of the smallest and largest degrees
+            for &pol in &[1 << 8, (1 << 57) - 1] {
+                for &window_size in &[1, 1024] {
+                    assert_edges_consistent(
+                        || Rabin::with_polynomial(pol, window_size),
+                        |e, buf| e.find_chunk_edge(buf),
+                    );
+                }
+            }
+        }
+
+        #[cfg(feature = "rabinkarp")]
+        #[test]
+        fn rabinkarp() {
+            use crate::rabinkarp;
+
+            for &window_size in &[1, 1024] {
+                let mut rabinkarp = RabinKarp::with_window_size(window_size);
+                for data in inputs() {
+                    rabinkarp.update_block(&data);
+                    let window = &data[data.len().saturating_sub(window_size)..];
+                    assert_eq!(rabinkarp.digest(), rabinkarp::checksum(window));
+                }
+                assert_edges_consistent(
+                    || RabinKarp::with_window_size(window_size),
+                    |e, buf| e.find_chunk_edge(buf),
+                );
+            }
+        }
+
+        #[cfg(feature = "rsync")]
+        #[test]
+        fn rsync() {
+            use crate::rsync;
+
+            for &window_size in &[1, 1024] {
+                let mut rsync = RsyncSum::with_window_size(window_size);
+                for data in inputs() {
+                    rsync.update_block(&data);
+                    let window = &data[data.len().saturating_sub(window_size)..];
+                    assert_eq!(rsync.digest(), rsync::checksum(window));
+                }
+                assert_edges_consistent(
+                    || RsyncSum::with_window_size(window_size),
+                    |e, buf| e.find_chunk_edge(buf),
+                );
+            }
+        }
+    }
+
+    /// Golden outputs of the default-configured engines.
+    ///
+    /// These have to hold under every feature combination. Optimized code
+    /// paths must be checked with `assert_default_output_unchanged`, and
+    /// anything that legitimately changes output (keys, seeds, custom tables)
+    /// has to be explicitly opted into, never enabled by a feature alone.
+    #[cfg(any(feature = "bup", feature = "gear"))]
+    mod determinism {
+        use super::*;
+        use std::fmt::Debug;
+
+        /// Compare the digest of `rand_data(1024)` and the chunk edges found
+        /// in `rand_data(128 * 1024)` using `cond` against recorded values.
+        fn assert_default_output_unchanged<E, F>(
+            cond: F,
+            expected_digest: E::Digest,
+            expected_edges: &[(usize, E::Digest)],
+        ) where
+            E: Engine + Default,
+            E::Digest: PartialEq + Debug,
+            F: Fn(&E) -> bool,
+        {
+            let mut engine = E::default();
+            engine.roll(&rand_data(1024));
+            assert_eq!(engine.digest(), expected_digest);
+
+            let mut engine = E::default();
+            let data = rand_data(128 * 1024);
+            let mut edges = vec![];
+            let mut ofs = 0;
+            while let Some((i, digest)) = engine.find_chunk_edge_cond(&data[ofs..], &cond) {
+                ofs += i;
+                edges.push((ofs, digest));
+            }
+            assert_eq!(edges, expected_edges);
+        }
+
+        #[cfg(feature = "bup")]
+        #[test]
+        fn bup() {
+            assert_default_output_unchanged::<Bup, _>(
+                |e| e.digest() & 0x1fff == 0x1fff,
+                0x294b0142,
+                &[
+                    (190, 0x26f31fff),
+                    (3067, 0x25ecffff),
+                    (6899, 0x26bb9fff),
+                    (8494, 0x2874ffff),
+                    (12698, 0x2ab47fff),
+                    (42099, 0x2a51ffff),
+                    (42670, 0x23069fff),
+                    (43167, 0x2ce79fff),
+                    (56906, 0x25f7dfff),
+                    (79029, 0x2e0dbfff),
+                    (90586, 0x29f67fff),
+                    (90735, 0x2802ffff),
+                    (117490, 0x26adffff),
+                    (118656, 0x28831fff),
+                ],
+            );
+        }
+
+        #[cfg(feature = "gear")]
+        #[test]
+        fn gear() {
+            assert_default_output_unchanged::<Gear, _>(
+                |e| e.digest() >> 51 == 0,
+                0x4cf6c24551e670aa,
+                &[
+                    (2737, 0x000254939954c17c),
+                    (2738, 0x0000d9e11053edd8),
+                    (25838, 0x0007982bb9fe3f1c),
+                    (28693, 0x000656bc7754d9ed),
+                    (40528, 0x0004d9352d1c2160),
+                    (47929, 0x0001498a66161695),
+                    (50174, 0x0006c9c199076d8d),
+                    (50462, 0x0000d53f0eee25e9),
+                    (71397, 0x0006d19f73e4dc9f),
+                    (71398, 0x000070ffad33e24e),
+                    (111898, 0x0001032265b39307),
+                    (112700, 0x0006401e68c9e8bb),
+                    (117612, 0x0002d9f21062d39e),
+                    (120489, 0x0000b97c4376431a),
+                    (123814, 0x0002660f05d83c57),
+                    (127649, 0x0007cef0bc59143d),
+                ],
+            );
+        }
+    }
 }
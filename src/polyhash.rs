@@ -0,0 +1,168 @@
+use super::Engine;
+use std::default::Default;
+
+pub type Digest = u64;
+
+/// Default chunk size used by `polyhash`
+pub const CHUNK_SIZE: u32 = 1 << CHUNK_BITS;
+
+/// Default chunk size used by `polyhash` (log2)
+pub const CHUNK_BITS: u32 = 13;
+
+/// Default window size used by `polyhash`
+pub const WINDOW_SIZE: usize = 64;
+
+/// Default multiplier used by `polyhash`
+pub const MULTIPLIER: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Multiply-and-add rolling hash, as used for substring search
+///
+/// The digest of a window `b[0..W]` (oldest byte first) is
+/// `sum(b[i] * B^(W - 1 - i))` modulo 2^64, where `B` is the multiplier.
+/// Low bits only depend on the low bits of the input, so edges are decided
+/// by the top bits.
+pub struct PolyHash<const W: usize = WINDOW_SIZE> {
+    digest: Digest,
+    multiplier: u64,
+    /// `B^W`, the factor of the byte leaving the window
+    pow_cache: u64,
+    window: [u8; W],
+    wofs: usize,
+    chunk_bits: u32,
+}
+
+impl<const W: usize> Default for PolyHash<W> {
+    fn default() -> Self {
+        Self::with_multiplier(MULTIPLIER)
+    }
+}
+
+impl<const W: usize> Engine for PolyHash<W> {
+    type Digest = Digest;
+
+    #[inline(always)]
+    fn roll_byte(&mut self, newch: u8) {
+        // See `Bup::roll_byte`: `wofs` is always less than `W`
+        let prevch = unsafe { *self.window.get_unchecked(self.wofs) };
+        self.digest = self
+            .digest
+            .wrapping_mul(self.multiplier)
+            .wrapping_add(newch as u64)
+            .wrapping_sub(self.pow_cache.wrapping_mul(prevch as u64));
+        unsafe { *self.window.get_unchecked_mut(self.wofs) = newch };
+        self.wofs = (self.wofs + 1) % W;
+    }
+
+    fn roll(&mut self, buf: &[u8]) {
+        crate::roll_windowed(self, buf);
+    }
+
+    fn window_size(&self) -> usize {
+        W
+    }
+
+    #[inline(always)]
+    fn digest(&self) -> Digest {
+        self.digest
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.digest = 0;
+        self.window = [0; W];
+        self.wofs = 0;
+    }
+}
+
+impl PolyHash {
+    /// Create new PolyHash engine with default chunking settings
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create new PolyHash engine with custom chunking settings
+    ///
+    /// `chunk_bits` is number of bits that need to match in
+    /// the edge condition. `CHUNK_BITS` constant is the default.
+    ///
+    /// Panics if `chunk_bits` is 64 or more.
+    pub fn new_with_chunk_bits(chunk_bits: u32) -> Self {
+        Self::with_chunk_bits(chunk_bits)
+    }
+}
+
+impl<const W: usize> PolyHash<W> {
+    /// Create new PolyHash engine of any window size with custom chunking
+    /// settings
+    ///
+    /// See `PolyHash::new_with_chunk_bits`.
+    pub fn with_chunk_bits(chunk_bits: u32) -> Self {
+        assert!(chunk_bits < 64);
+        PolyHash {
+            chunk_bits,
+            ..Default::default()
+        }
+    }
+
+    /// Create new PolyHash engine using `multiplier`, which should be odd
+    ///
+    /// Panics if `W` is zero.
+    pub fn with_multiplier(multiplier: u64) -> Self {
+        assert!(W > 0);
+        PolyHash {
+            digest: 0,
+            multiplier,
+            pow_cache: (0..W).fold(1, |pow: u64, _| pow.wrapping_mul(multiplier)),
+            window: [0; W],
+            wofs: 0,
+            chunk_bits: CHUNK_BITS,
+        }
+    }
+
+    /// Find chunk edge using PolyHash defaults.
+    ///
+    /// An edge is where the top `chunk_bits` bits are all zero.
+    ///
+    /// See `Engine::find_chunk_edge_cond`.
+    pub fn find_chunk_edge(&mut self, buf: &[u8]) -> Option<(usize, Digest)> {
+        let mask = !(Digest::MAX >> self.chunk_bits);
+        self.find_chunk_edge_cond(buf, |e: &Self| e.digest() & mask == 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanorand::{Rng, WyRand};
+
+    fn reference(data: &[u8], multiplier: u64) -> Digest {
+        data.iter().fold(0, |h: u64, &b| {
+            h.wrapping_mul(multiplier).wrapping_add(b as u64)
+        })
+    }
+
+    fn check_against_reference<const W: usize>(multiplier: u64) {
+        let polyhash = PolyHash::<W>::with_multiplier(multiplier);
+        crate::tests::check_against_reference(polyhash, |window| reference(window, multiplier));
+    }
+
+    #[test]
+    fn same_as_reference() {
+        check_against_reference::<64>(MULTIPLIER);
+        check_against_reference::<1>(MULTIPLIER);
+        check_against_reference::<48>(257);
+        check_against_reference::<1000>(0x1_0000_01b3);
+    }
+
+    #[test]
+    fn reset_keeps_multiplier() {
+        let mut data = vec![0u8; 1024];
+        WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+
+        let mut polyhash = PolyHash::<48>::with_multiplier(257);
+        polyhash.roll(&data);
+        polyhash.reset();
+        polyhash.roll(&data[..100]);
+        assert_eq!(polyhash.digest(), reference(&data[52..100], 257));
+    }
+}
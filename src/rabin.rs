@@ -0,0 +1,314 @@
+use super::Engine;
+use std::default::Default;
+
+pub type Digest = u64;
+
+/// Default chunk size used by `rabin`
+pub const CHUNK_SIZE: u32 = 1 << CHUNK_BITS;
+
+/// Default chunk size used by `rabin` (log2)
+pub const CHUNK_BITS: u32 = 13;
+
+/// Default window size used by `rabin`
+pub const WINDOW_SIZE: usize = 64;
+
+/// Default irreducible polynomial used by `rabin` (degree 53)
+pub const POLYNOMIAL: u64 = 0x3DA3358B4DC173;
+
+/// Rabin fingerprint over GF(2) with a configurable polynomial
+///
+/// The digest is the window, read as a polynomial with one coefficient per
+/// bit, reduced modulo the irreducible polynomial. Strongly based on
+/// https://github.com/restic/chunker/blob/master/chunker.go
+pub struct Rabin {
+    digest: Digest,
+    window: Vec<u8>,
+    wofs: usize,
+    pol_shift: u32,
+    out_table: [Digest; 256],
+    mod_table: [Digest; 256],
+    chunk_bits: u32,
+}
+
+impl Default for Rabin {
+    fn default() -> Self {
+        Rabin::with_polynomial(POLYNOMIAL, WINDOW_SIZE)
+    }
+}
+
+impl Engine for Rabin {
+    type Digest = Digest;
+
+    #[inline(always)]
+    fn roll_byte(&mut self, newch: u8) {
+        let prevch = self.window[self.wofs];
+        self.window[self.wofs] = newch;
+        self.wofs = (self.wofs + 1) % self.window.len();
+
+        let digest = self.digest ^ self.out_table[prevch as usize];
+        let index = (digest >> self.pol_shift) as usize;
+        self.digest = ((digest << 8) | newch as Digest) ^ self.mod_table[index];
+    }
+
+    fn roll(&mut self, buf: &[u8]) {
+        crate::roll_windowed(self, buf);
+    }
+
+    fn window_size(&self) -> usize {
+        self.window.len()
+    }
+
+    #[inline(always)]
+    fn digest(&self) -> Digest {
+        self.digest
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.window.iter_mut().for_each(|b| *b = 0);
+        self.wofs = 0;
+        self.digest = 0;
+        // Like restic, start from a window that ends in a single 1 byte
+        self.roll_byte(1);
+    }
+}
+
+impl Rabin {
+    /// Create new Rabin engine with default chunking settings
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create new Rabin engine with custom chunking settings
+    ///
+    /// `chunk_bits` is number of bits that need to match in
+    /// the edge condition. `CHUNK_BITS` constant is the default.
+    ///
+    /// Panics if `chunk_bits` is 64 or more.
+    pub fn new_with_chunk_bits(chunk_bits: u32) -> Self {
+        assert!(chunk_bits < 64);
+        Rabin {
+            chunk_bits,
+            ..Default::default()
+        }
+    }
+
+    /// Create new Rabin engine using the irreducible polynomial `pol` over a
+    /// window of `window_size` bytes
+    ///
+    /// Panics if the degree of `pol` is not between 8 and 56, or if
+    /// `window_size` is zero.
+    pub fn with_polynomial(pol: u64, window_size: usize) -> Self {
+        assert!(window_size > 0);
+        assert!(
+            (1 << 8..1 << 57).contains(&pol),
+            "polynomial degree must be 8..=56"
+        );
+        let k = deg(pol);
+
+        // mod_table[b] both reduces the 8 bits shifted above the degree of
+        // `pol` and clears them, in a single xor
+        let mut mod_table = [0; 256];
+        for (b, m) in mod_table.iter_mut().enumerate() {
+            let shifted = (b as u64) << k;
+            *m = modulo(shifted, pol) | shifted;
+        }
+        let pol_shift = k - 8;
+
+        // out_table[b] is the hash of `b` followed by `window_size - 1`
+        // zeroes, so xoring it in removes `b` from the front of the window.
+        // Hashing is linear, so only single-bit bytes are hashed directly.
+        let mut out_table = [0; 256];
+        for bit in 0..8 {
+            let mut h: Digest = 1 << bit;
+            for _ in 1..window_size {
+                h = (h << 8) ^ mod_table[(h >> pol_shift) as usize];
+            }
+            for (b, out) in out_table.iter_mut().enumerate() {
+                if b & (1 << bit) != 0 {
+                    *out ^= h;
+                }
+            }
+        }
+
+        let mut rabin = Rabin {
+            digest: 0,
+            window: vec![0; window_size],
+            wofs: 0,
+            pol_shift,
+            out_table,
+            mod_table,
+            chunk_bits: CHUNK_BITS,
+        };
+        rabin.reset();
+        rabin
+    }
+
+    /// Find chunk edge using Rabin defaults.
+    ///
+    /// As in restic, an edge is where the low `chunk_bits` bits are all zero.
+    ///
+    /// See `Engine::find_chunk_edge_cond`.
+    pub fn find_chunk_edge(&mut self, buf: &[u8]) -> Option<(usize, Digest)> {
+        let chunk_mask = (1 << self.chunk_bits) - 1;
+        self.find_chunk_edge_cond(buf, |e: &Rabin| e.digest() & chunk_mask == 0)
+    }
+}
+
+/// Derive a random irreducible polynomial of degree 53 from `seed`
+///
+/// As in restic, candidates with the constant term set are drawn until one
+/// is irreducible, so that every repository can chunk with its own
+/// polynomial. The same seed always gives the same polynomial.
+pub fn random_polynomial(seed: u64) -> u64 {
+    let mut n = 0;
+    loop {
+        let pol = (crate::splitmix64(seed, n) & ((1 << 54) - 1)) | (1 << 53) | 1;
+        if is_irreducible(pol) {
+            return pol;
+        }
+        n += 1;
+    }
+}
+
+/// Whether `pol` is irreducible over GF(2)
+///
+/// Uses Ben-Or's test, like restic: `pol` of degree `n` is irreducible if
+/// `gcd(x^(2^i) - x, pol)` is 1 for every `i` up to `n / 2`.
+pub fn is_irreducible(pol: u64) -> bool {
+    if pol < 2 {
+        return false;
+    }
+    let x = modulo(2, pol);
+    let mut x_pow = x;
+    for _ in 0..deg(pol) / 2 {
+        x_pow = mulmod(x_pow, x_pow, pol);
+        if gcd(pol, x_pow ^ x) != 1 {
+            return false;
+        }
+    }
+    true
+}
+
+/// Degree of the polynomial `pol`
+fn deg(pol: u64) -> u32 {
+    63 - pol.leading_zeros()
+}
+
+/// `x` modulo `pol` over GF(2)
+fn modulo(mut x: u64, pol: u64) -> u64 {
+    while x != 0 && deg(x) >= deg(pol) {
+        x ^= pol << (deg(x) - deg(pol));
+    }
+    x
+}
+
+/// `a * b` modulo `pol` over GF(2), for `a` and `b` already reduced
+fn mulmod(a: u64, b: u64, pol: u64) -> u64 {
+    let top = 1 << deg(pol);
+    (0..64).rev().fold(0, |mut product, bit| {
+        product <<= 1;
+        if product & top != 0 {
+            product ^= pol;
+        }
+        if b & (1 << bit) != 0 {
+            product ^= a;
+        }
+        product
+    })
+}
+
+/// Greatest common divisor of `a` and `b` over GF(2)
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let r = modulo(a, b);
+        a = b;
+        b = r;
+    }
+    a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanorand::{Rng, WyRand};
+
+    fn reference(data: &[u8], pol: u64) -> Digest {
+        data.iter()
+            .fold(0, |hash, &b| modulo((hash << 8) | b as u64, pol))
+    }
+
+    fn check_against_reference(pol: u64, window_size: usize) {
+        let rabin = Rabin::with_polynomial(pol, window_size);
+        crate::tests::check_against_reference(rabin, |window| reference(window, pol));
+    }
+
+    #[test]
+    fn same_as_reference() {
+        check_against_reference(POLYNOMIAL, 64);
+        check_against_reference(POLYNOMIAL, 1);
+        check_against_reference(POLYNOMIAL, 48);
+        check_against_reference(POLYNOMIAL, 100);
+        // x^8 + x^4 + x^3 + x + 1
+        check_against_reference(0x11b, 31);
+    }
+
+    #[test]
+    fn reset_restores_initial_fingerprint() {
+        let mut data = vec![0u8; 1024];
+        WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+
+        let mut rabin = Rabin::with_polynomial(POLYNOMIAL, 48);
+        let initial = rabin.digest();
+        assert_eq!(initial, 1);
+
+        rabin.roll(&data);
+        rabin.reset();
+        assert_eq!(rabin.digest(), initial);
+
+        let mut fresh = Rabin::with_polynomial(POLYNOMIAL, 48);
+        for &b in &data[..10] {
+            rabin.roll_byte(b);
+            fresh.roll_byte(b);
+            assert_eq!(rabin.digest(), fresh.digest());
+        }
+    }
+
+    #[test]
+    fn irreducible_polynomials() {
+        // From restic's chunker tests
+        assert!(is_irreducible(POLYNOMIAL));
+        assert!(is_irreducible(0x2482734cacca49));
+        assert!(!is_irreducible(0x38f1e565e288df));
+        assert!(!is_irreducible(0x30a8295b9d5c91));
+        assert!(!is_irreducible(0x255f4350b962cb));
+        assert!(!is_irreducible(0x3143d0464b3299));
+
+        // x + 1, x^2 + x + 1, x^8 + x^4 + x^3 + x + 1
+        assert!(is_irreducible(0x3));
+        assert!(is_irreducible(0x7));
+        assert!(is_irreducible(0x11b));
+        // x^2 + 1 = (x + 1)^2, and POLYNOMIAL * x
+        assert!(!is_irreducible(0x5));
+        assert!(!is_irreducible(POLYNOMIAL << 1));
+        assert!(!is_irreducible(1));
+    }
+
+    #[test]
+    fn random_polynomial_is_deterministic() {
+        for seed in 0..8 {
+            let pol = random_polynomial(seed);
+            assert_eq!(deg(pol), 53);
+            assert!(is_irreducible(pol));
+            assert_eq!(pol, random_polynomial(seed));
+        }
+        assert_ne!(random_polynomial(1), random_polynomial(2));
+        check_against_reference(random_polynomial(1), 64);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_small_polynomial() {
+        Rabin::with_polynomial(0x7, WINDOW_SIZE);
+    }
+}
@@ -0,0 +1,204 @@
+use super::Engine;
+use std::default::Default;
+
+pub type Digest = u32;
+
+/// Default chunk size used by `rabinkarp`
+pub const CHUNK_SIZE: u32 = 1 << CHUNK_BITS;
+
+/// Default chunk size used by `rabinkarp` (log2)
+pub const CHUNK_BITS: u32 = 13;
+
+/// Default window size used by `rabinkarp`
+pub const WINDOW_SIZE: usize = 64;
+
+/// Hash of no bytes at all
+const SEED: u32 = 1;
+
+/// Multiplier per byte
+const MULT: u32 = 0x0810_4225;
+
+/// Adjustment removing the seed's extra multiplication, `MULT - 1`
+const ADJ: u32 = 0x0810_4224;
+
+/// RabinKarp rolling hash, with the constants of librsync 2.2 and later
+///
+/// The digest of bytes `b[0..n]` is
+/// `SEED * MULT^n + sum(b[i] * MULT^(n - 1 - i))`, modulo 2^32. Until the
+/// window is full, it is the hash of every byte rolled since the last reset,
+/// like librsync's `rabinkarp_rollin`.
+///
+/// Strongly based on
+/// https://github.com/librsync/librsync/blob/master/src/rabinkarp.h
+///
+/// The digests have not been checked against librsync's own, so this is
+/// not meant for producing signatures for `rdiff`.
+pub struct RabinKarp {
+    hash: u32,
+    /// `MULT^count`
+    mult: u32,
+    count: usize,
+    window: Vec<u8>,
+    wofs: usize,
+    chunk_bits: u32,
+}
+
+impl Default for RabinKarp {
+    fn default() -> Self {
+        RabinKarp::with_window_size(WINDOW_SIZE)
+    }
+}
+
+impl Engine for RabinKarp {
+    type Digest = Digest;
+
+    #[inline(always)]
+    fn roll_byte(&mut self, newch: u8) {
+        let prevch = self.window[self.wofs];
+        self.window[self.wofs] = newch;
+        self.wofs = (self.wofs + 1) % self.window.len();
+
+        if self.count < self.window.len() {
+            // rabinkarp_rollin
+            self.hash = self.hash.wrapping_mul(MULT).wrapping_add(newch as u32);
+            self.mult = self.mult.wrapping_mul(MULT);
+            self.count += 1;
+        } else {
+            // rabinkarp_rotate
+            self.hash = self
+                .hash
+                .wrapping_mul(MULT)
+                .wrapping_add(newch as u32)
+                .wrapping_sub(self.mult.wrapping_mul((prevch as u32).wrapping_add(ADJ)));
+        }
+    }
+
+    fn roll(&mut self, buf: &[u8]) {
+        crate::roll_windowed(self, buf);
+    }
+
+    fn window_size(&self) -> usize {
+        self.window.len()
+    }
+
+    #[inline(always)]
+    fn digest(&self) -> Digest {
+        self.hash
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.hash = SEED;
+        self.mult = 1;
+        self.count = 0;
+        self.window.iter_mut().for_each(|b| *b = 0);
+        self.wofs = 0;
+    }
+}
+
+impl RabinKarp {
+    /// Create new RabinKarp engine with default chunking settings
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create new RabinKarp engine with custom chunking settings
+    ///
+    /// `chunk_bits` is number of bits that need to match in
+    /// the edge condition. `CHUNK_BITS` constant is the default.
+    ///
+    /// Panics if `chunk_bits` is 32 or more.
+    pub fn new_with_chunk_bits(chunk_bits: u32) -> Self {
+        assert!(chunk_bits < 32);
+        RabinKarp {
+            chunk_bits,
+            ..Default::default()
+        }
+    }
+
+    /// Create new RabinKarp engine rolling over `window_size` bytes, i.e.
+    /// the librsync block length
+    ///
+    /// Panics if `window_size` is zero.
+    pub fn with_window_size(window_size: usize) -> Self {
+        assert!(window_size > 0);
+        RabinKarp {
+            hash: SEED,
+            mult: 1,
+            count: 0,
+            window: vec![0; window_size],
+            wofs: 0,
+            chunk_bits: CHUNK_BITS,
+        }
+    }
+
+    /// Start over with the hash of `block`, like librsync does for every
+    /// block of the basis file
+    ///
+    /// The digest then equals `checksum(block)` as long as `block` is no
+    /// longer than the window.
+    pub fn update_block(&mut self, block: &[u8]) {
+        self.reset();
+        self.roll(block);
+    }
+
+    /// Find chunk edge using RabinKarp defaults.
+    ///
+    /// An edge is where the low `chunk_bits` bits are all zero.
+    ///
+    /// See `Engine::find_chunk_edge_cond`.
+    pub fn find_chunk_edge(&mut self, buf: &[u8]) -> Option<(usize, Digest)> {
+        let chunk_mask = (1 << self.chunk_bits) - 1;
+        self.find_chunk_edge_cond(buf, |e: &RabinKarp| e.digest() & chunk_mask == 0)
+    }
+}
+
+/// RabinKarp hash of a whole block, after librsync's `rabinkarp_update`
+pub fn checksum(block: &[u8]) -> Digest {
+    block.iter().fold(SEED, |hash, &b| {
+        hash.wrapping_mul(MULT).wrapping_add(b as u32)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanorand::{Rng, WyRand};
+
+    #[test]
+    fn checksum_vectors() {
+        assert_eq!(checksum(b""), SEED);
+        assert_eq!(checksum(b"a"), 0x0810_4286);
+        // 0x0810_4286 * MULT + 0x62, modulo 2^32
+        assert_eq!(checksum(b"ab"), 0xb3e0_29c0);
+        assert_eq!(ADJ, MULT - 1);
+    }
+
+    #[test]
+    fn rolling_same_as_checksum() {
+        let mut data = vec![0u8; 4096];
+        WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+
+        for &window_size in &[1, 64, 700] {
+            let mut rabinkarp = RabinKarp::with_window_size(window_size);
+            for (i, &b) in data.iter().enumerate() {
+                rabinkarp.roll_byte(b);
+                let start = (i + 1).saturating_sub(window_size);
+                assert_eq!(rabinkarp.digest(), checksum(&data[start..=i]));
+            }
+        }
+    }
+
+    #[test]
+    fn update_block() {
+        let mut data = vec![0u8; 1024];
+        WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+
+        let mut rabinkarp = RabinKarp::with_window_size(700);
+        rabinkarp.roll(&data);
+        for block in data.chunks(700) {
+            rabinkarp.update_block(block);
+            assert_eq!(rabinkarp.digest(), checksum(block));
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use super::Engine;
+use std::default::Default;
+
+pub type Digest = u32;
+
+/// Default chunk size used by `rsync`
+pub const CHUNK_SIZE: u32 = 1 << CHUNK_BITS;
+
+/// Default chunk size used by `rsync` (log2)
+pub const CHUNK_BITS: u32 = 13;
+
+/// Default window size used by `rsync`
+pub const WINDOW_SIZE: usize = 64;
+
+/// Weak rolling checksum used by `rsync`
+///
+/// Unlike `Bup`, there is no character offset, and bytes are summed as
+/// signed chars just like rsync does. The digest is `s1 | (s2 << 16)`,
+/// with both sums modulo 2^16.
+///
+/// Strongly based on
+/// https://github.com/WayneD/rsync/blob/master/checksum.c
+/// https://github.com/WayneD/rsync/blob/master/match.c
+pub struct RsyncSum {
+    s1: u32,
+    s2: u32,
+    window: Vec<u8>,
+    wofs: usize,
+    chunk_bits: u32,
+}
+
+impl Default for RsyncSum {
+    fn default() -> Self {
+        RsyncSum::with_window_size(WINDOW_SIZE)
+    }
+}
+
+/// The value rsync adds to its sums for `byte`
+#[inline(always)]
+fn signed(byte: u8) -> u32 {
+    byte as i8 as u32
+}
+
+impl Engine for RsyncSum {
+    type Digest = Digest;
+
+    #[inline(always)]
+    fn roll_byte(&mut self, newch: u8) {
+        let prevch = self.window[self.wofs];
+        self.window[self.wofs] = newch;
+        self.wofs = (self.wofs + 1) % self.window.len();
+
+        let n = self.window.len() as u32;
+        self.s1 = self
+            .s1
+            .wrapping_add(signed(newch))
+            .wrapping_sub(signed(prevch));
+        self.s2 = self
+            .s2
+            .wrapping_add(self.s1)
+            .wrapping_sub(n.wrapping_mul(signed(prevch)));
+    }
+
+    fn roll(&mut self, buf: &[u8]) {
+        crate::roll_windowed(self, buf);
+    }
+
+    fn window_size(&self) -> usize {
+        self.window.len()
+    }
+
+    #[inline(always)]
+    fn digest(&self) -> Digest {
+        (self.s1 & 0xffff) | (self.s2 << 16)
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.s1 = 0;
+        self.s2 = 0;
+        self.window.iter_mut().for_each(|b| *b = 0);
+        self.wofs = 0;
+    }
+}
+
+impl RsyncSum {
+    /// Create new RsyncSum engine with default chunking settings
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create new RsyncSum engine with custom chunking settings
+    ///
+    /// `chunk_bits` is number of bits that need to match in
+    /// the edge condition. `CHUNK_BITS` constant is the default.
+    ///
+    /// Panics if `chunk_bits` is 32 or more.
+    pub fn new_with_chunk_bits(chunk_bits: u32) -> Self {
+        assert!(chunk_bits < 32);
+        RsyncSum {
+            chunk_bits,
+            ..Default::default()
+        }
+    }
+
+    /// Create new RsyncSum engine rolling over `window_size` bytes, i.e. the
+    /// rsync block length
+    ///
+    /// Panics if `window_size` is zero.
+    pub fn with_window_size(window_size: usize) -> Self {
+        assert!(window_size > 0);
+        RsyncSum {
+            s1: 0,
+            s2: 0,
+            window: vec![0; window_size],
+            wofs: 0,
+            chunk_bits: CHUNK_BITS,
+        }
+    }
+
+    /// Start over with the sum of `block`, as rsync does for every block of
+    /// the basis file
+    ///
+    /// The digest then equals `checksum(block)` as long as `block` is no
+    /// longer than the window.
+    pub fn update_block(&mut self, block: &[u8]) {
+        self.reset();
+        self.roll(block);
+    }
+
+    /// Find chunk edge using RsyncSum defaults.
+    ///
+    /// An edge is where the low `chunk_bits` bits are all zero.
+    ///
+    /// See `Engine::find_chunk_edge_cond`.
+    pub fn find_chunk_edge(&mut self, buf: &[u8]) -> Option<(usize, Digest)> {
+        let chunk_mask = (1 << self.chunk_bits) - 1;
+        self.find_chunk_edge_cond(buf, |e: &RsyncSum| e.digest() & chunk_mask == 0)
+    }
+}
+
+/// rsync's weak checksum of a whole block (`get_checksum1`)
+pub fn checksum(block: &[u8]) -> Digest {
+    let (mut s1, mut s2) = (0u32, 0u32);
+    for &b in block {
+        s1 = s1.wrapping_add(signed(b));
+        s2 = s2.wrapping_add(s1);
+    }
+    (s1 & 0xffff) | (s2 << 16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanorand::{Rng, WyRand};
+
+    #[test]
+    fn checksum_vectors() {
+        assert_eq!(checksum(b""), 0);
+        assert_eq!(checksum(b"a"), 0x0061_0061);
+        // s1 = 0x61 + 0x62 + 0x63, s2 = 3 * 0x61 + 2 * 0x62 + 0x63
+        assert_eq!(checksum(b"abc"), 0x024a_0126);
+        // Bytes are signed: 0xff counts as -1
+        assert_eq!(checksum(&[0xff]), 0xffff_ffff);
+        assert_eq!(checksum(&[0x80, 0x01]), 0xff01_ff81);
+    }
+
+    #[test]
+    fn rolling_same_as_checksum() {
+        let mut data = vec![0u8; 4096];
+        WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+
+        for &window_size in &[1, 64, 700] {
+            let mut sum = RsyncSum::with_window_size(window_size);
+            for (i, &b) in data.iter().enumerate() {
+                sum.roll_byte(b);
+                let start = (i + 1).saturating_sub(window_size);
+                assert_eq!(sum.digest(), checksum(&data[start..=i]));
+            }
+        }
+    }
+
+    #[test]
+    fn update_block() {
+        let mut data = vec![0u8; 1024];
+        WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+
+        let mut sum = RsyncSum::with_window_size(700);
+        sum.roll(&data);
+        for block in data.chunks(700) {
+            sum.update_block(block);
+            assert_eq!(sum.digest(), checksum(block));
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use super::Engine;
+
+/// Two engines rolled over the same bytes
+///
+/// The digest is the pair of both digests, so edges can be found under
+/// either scheme, or both, in a single pass.
+#[derive(Default)]
+pub struct Tandem<A, B>(pub A, pub B);
+
+impl<A: Engine, B: Engine> Engine for Tandem<A, B> {
+    type Digest = (A::Digest, B::Digest);
+
+    #[inline(always)]
+    fn roll_byte(&mut self, byte: u8) {
+        self.0.roll_byte(byte);
+        self.1.roll_byte(byte);
+    }
+
+    fn roll(&mut self, buf: &[u8]) {
+        self.0.roll(buf);
+        self.1.roll(buf);
+    }
+
+    fn window_size(&self) -> usize {
+        self.0.window_size().max(self.1.window_size())
+    }
+
+    #[inline(always)]
+    fn digest(&self) -> Self::Digest {
+        (self.0.digest(), self.1.digest())
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.0.reset();
+        self.1.reset();
+    }
+}
+
+impl<A: Engine, B: Engine> Tandem<A, B> {
+    /// Create new Tandem engine rolling `a` and `b`
+    pub fn new(a: A, b: B) -> Self {
+        Tandem(a, b)
+    }
+
+    /// Find the end of the chunk under either scheme, i.e. where `cond_a`
+    /// holds for the first engine or `cond_b` for the second.
+    ///
+    /// See `Engine::find_chunk_edge_cond`.
+    pub fn find_chunk_edge_either<F, G>(
+        &mut self,
+        buf: &[u8],
+        cond_a: F,
+        cond_b: G,
+    ) -> Option<(usize, <Self as Engine>::Digest)>
+    where
+        F: Fn(&A) -> bool,
+        G: Fn(&B) -> bool,
+    {
+        self.find_chunk_edge_cond(buf, |e| cond_a(&e.0) || cond_b(&e.1))
+    }
+
+    /// Find the end of the chunk under both schemes at once, i.e. where
+    /// `cond_a` holds for the first engine and `cond_b` for the second.
+    ///
+    /// See `Engine::find_chunk_edge_cond`.
+    pub fn find_chunk_edge_both<F, G>(
+        &mut self,
+        buf: &[u8],
+        cond_a: F,
+        cond_b: G,
+    ) -> Option<(usize, <Self as Engine>::Digest)>
+    where
+        F: Fn(&A) -> bool,
+        G: Fn(&B) -> bool,
+    {
+        self.find_chunk_edge_cond(buf, |e| cond_a(&e.0) && cond_b(&e.1))
+    }
+}
+
+#[cfg(all(test, feature = "bup", feature = "gear"))]
+mod tests {
+    use super::*;
+    use crate::{Bup, Gear};
+    use nanorand::{Rng, WyRand};
+
+    fn rand_data(len: usize) -> Vec<u8> {
+        let mut data = vec![0u8; len];
+        WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+        data
+    }
+
+    #[test]
+    fn same_as_separate_engines() {
+        let data = rand_data(4096);
+        let mut tandem = Tandem(Bup::new(), Gear::new());
+        let mut bup = Bup::new();
+        let mut gear = Gear::new();
+        for &b in &data {
+            tandem.roll_byte(b);
+            bup.roll_byte(b);
+            gear.roll_byte(b);
+            assert_eq!(tandem.digest(), (bup.digest(), gear.digest()));
+        }
+
+        tandem.reset();
+        tandem.roll(&data[..1000]);
+        assert_eq!(tandem.digest().0, {
+            let mut bup = Bup::new();
+            bup.roll(&data[..1000]);
+            bup.digest()
+        });
+    }
+
+    #[test]
+    fn either_and_both() {
+        let data = rand_data(256 * 1024);
+        let bup_cond = |e: &Bup| e.digest() & 0x3f == 0x3f;
+        let gear_cond = |e: &Gear| e.digest() >> 58 == 0;
+
+        let mut tandem = Tandem::new(Bup::new(), Gear::new());
+        let mut remaining = &data[..];
+        while let Some((i, digest)) = tandem.find_chunk_edge_either(remaining, bup_cond, gear_cond)
+        {
+            let mut bup = Bup::new();
+            let mut gear = Gear::new();
+            let bup_edge = bup.find_chunk_edge_cond(remaining, bup_cond).map(|e| e.0);
+            let gear_edge = gear.find_chunk_edge_cond(remaining, gear_cond).map(|e| e.0);
+            assert_eq!(Some(i), bup_edge.into_iter().chain(gear_edge).min());
+            assert!(digest.0 & 0x3f == 0x3f || digest.1 >> 58 == 0);
+            remaining = &remaining[i..];
+        }
+
+        let mut tandem = Tandem::new(Bup::new(), Gear::new());
+        let mut remaining = &data[..];
+        let mut edges = 0;
+        while let Some((i, digest)) = tandem.find_chunk_edge_both(remaining, bup_cond, gear_cond) {
+            assert!(digest.0 & 0x3f == 0x3f && digest.1 >> 58 == 0);
+            remaining = &remaining[i..];
+            edges += 1;
+        }
+        assert!(edges > 0);
+    }
+}
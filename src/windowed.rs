@@ -0,0 +1,210 @@
+use super::Engine;
+
+/// Byte mixing functions turned into an `Engine` by `Windowed`
+///
+/// `Windowed` keeps the window itself, so implementations only say how a
+/// byte enters and leaves the running state.
+pub trait WindowFn {
+    /// Running state the digest is derived from
+    type State: Copy;
+    type Digest;
+
+    /// State of a window of `window_size` zero bytes
+    fn initial(&self, window_size: usize) -> Self::State;
+
+    /// Add `byte` to `state` as the newest byte of the window
+    fn fold(&self, state: Self::State, byte: u8) -> Self::State;
+
+    /// Remove `byte`, the oldest byte of a full window of `window_size`
+    /// bytes, from `state`
+    ///
+    /// This is always followed by a `fold` of the byte replacing it.
+    fn unfold(&self, state: Self::State, byte: u8, window_size: usize) -> Self::State;
+
+    /// Digest of `state`
+    fn digest(&self, state: &Self::State) -> Self::Digest;
+}
+
+/// Engine rolling the `WindowFn` `F` over a window of `W` bytes
+pub struct Windowed<F: WindowFn, const W: usize> {
+    f: F,
+    state: F::State,
+    initial: F::State,
+    window: [u8; W],
+    wofs: usize,
+}
+
+impl<F: WindowFn + Default, const W: usize> Default for Windowed<F, W> {
+    fn default() -> Self {
+        Windowed::new(F::default())
+    }
+}
+
+impl<F: WindowFn, const W: usize> Engine for Windowed<F, W> {
+    type Digest = F::Digest;
+
+    #[inline(always)]
+    fn roll_byte(&mut self, newch: u8) {
+        // See `Bup::roll_byte`: `wofs` is always less than `W`
+        let prevch = unsafe { *self.window.get_unchecked(self.wofs) };
+        let state = self.f.unfold(self.state, prevch, W);
+        self.state = self.f.fold(state, newch);
+        unsafe { *self.window.get_unchecked_mut(self.wofs) = newch };
+        self.wofs = (self.wofs + 1) % W;
+    }
+
+    fn roll(&mut self, buf: &[u8]) {
+        crate::roll_windowed(self, buf);
+    }
+
+    fn window_size(&self) -> usize {
+        W
+    }
+
+    #[inline(always)]
+    fn digest(&self) -> F::Digest {
+        self.f.digest(&self.state)
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.state = self.initial;
+        self.window = [0; W];
+        self.wofs = 0;
+    }
+}
+
+impl<F: WindowFn, const W: usize> Windowed<F, W> {
+    /// Create new Windowed engine rolling `f`
+    ///
+    /// Panics if `W` is zero.
+    pub fn new(f: F) -> Self {
+        assert!(W > 0);
+        let initial = f.initial(W);
+        Windowed {
+            f,
+            state: initial,
+            initial,
+            window: [0; W],
+            wofs: 0,
+        }
+    }
+}
+
+/// `bup::step` split in two, for `Windowed` with a window of 64 bytes
+///
+/// Shared by the tests and benchmarks comparing `Windowed` with `Bup`; not
+/// part of the public API.
+#[cfg(feature = "bup")]
+#[doc(hidden)]
+#[derive(Default)]
+pub struct BupFn;
+
+#[cfg(feature = "bup")]
+impl WindowFn for BupFn {
+    type State = crate::bup::BupState;
+    type Digest = u32;
+
+    fn initial(&self, _window_size: usize) -> Self::State {
+        Default::default()
+    }
+
+    #[inline(always)]
+    fn fold(&self, state: Self::State, byte: u8) -> Self::State {
+        crate::bup::step(state, 0, byte)
+    }
+
+    /// Replace the oldest byte with a zero, which `fold` then drops
+    #[inline(always)]
+    fn unfold(&self, mut state: Self::State, byte: u8, window_size: usize) -> Self::State {
+        state.s1 -= byte as usize;
+        state.s2 -= window_size * byte as usize;
+        state
+    }
+
+    #[inline(always)]
+    fn digest(&self, state: &Self::State) -> u32 {
+        state.digest()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanorand::{Rng, WyRand};
+
+    /// Sum of the bytes in the window
+    #[derive(Default)]
+    struct MovingSum;
+
+    impl WindowFn for MovingSum {
+        type State = u32;
+        type Digest = u32;
+
+        fn initial(&self, _window_size: usize) -> u32 {
+            0
+        }
+
+        fn fold(&self, state: u32, byte: u8) -> u32 {
+            state + byte as u32
+        }
+
+        fn unfold(&self, state: u32, byte: u8, _window_size: usize) -> u32 {
+            state - byte as u32
+        }
+
+        fn digest(&self, state: &u32) -> u32 {
+            *state
+        }
+    }
+
+    fn rand_data(len: usize) -> Vec<u8> {
+        let mut data = vec![0u8; len];
+        WyRand::new_seed(0x01020304).fill_bytes(&mut data);
+        data
+    }
+
+    #[test]
+    fn moving_sum() {
+        let data = rand_data(1024);
+        let mut engine = Windowed::<MovingSum, 48>::default();
+        for (i, &b) in data.iter().enumerate() {
+            engine.roll_byte(b);
+            let window = &data[(i + 1).saturating_sub(48)..=i];
+            assert_eq!(engine.digest(), window.iter().map(|&b| b as u32).sum());
+        }
+    }
+
+    #[test]
+    fn chunk_edge_correct_digest() {
+        let data = rand_data(64 * 1024);
+        let mut engine1 = Windowed::<MovingSum, 16>::default();
+        let mut remaining = &data[..];
+        let mut edges = 0;
+        while let Some((i, digest)) =
+            engine1.find_chunk_edge_cond(remaining, |e| e.digest() % 64 == 0)
+        {
+            let mut engine2 = Windowed::<MovingSum, 16>::default();
+            engine2.roll(&remaining[..i]);
+            assert_eq!(engine2.digest(), digest);
+            assert_eq!(digest % 64, 0);
+            assert_eq!(engine1.digest(), 0);
+            remaining = &remaining[i..];
+            edges += 1;
+        }
+        assert!(edges > 100);
+    }
+
+    #[cfg(feature = "bup")]
+    #[test]
+    fn same_as_bup() {
+        let data = rand_data(4096);
+        let mut bup = crate::Bup::new();
+        let mut windowed = Windowed::<BupFn, 64>::default();
+        for &b in &data {
+            bup.roll_byte(b);
+            windowed.roll_byte(b);
+            assert_eq!(bup.digest(), windowed.digest());
+        }
+    }
+}